@@ -0,0 +1,142 @@
+use serde::Serialize;
+use zkwasm_rest_abi::StorageData;
+use crate::config::{BASIS_POINTS_PRECISION, BASIS_POINTS_TO_PRICE, CIRCUIT_BREAKER_COOLDOWN_TICKS, CIRCUIT_BREAKER_MOVE_BPS, CIRCUIT_BREAKER_WINDOW_TICKS};
+use crate::error::ERROR_INVALID_BREAKER_LIMITS;
+use crate::event::{insert_event, EVENT_CIRCUIT_BREAKER};
+use crate::market::Market;
+use crate::math_safe::safe_add;
+use crate::state::GLOBAL_STATE;
+
+/// Per-market thresholds of the circuit breaker, set by the admin.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct BreakerLimits {
+    pub move_bps: u64, // largest move of any outcome price within a window, in basis points of 1.0
+    pub window_ticks: u64,
+    pub cooldown_ticks: u64,
+}
+
+impl Default for BreakerLimits {
+    fn default() -> Self {
+        BreakerLimits {
+            move_bps: CIRCUIT_BREAKER_MOVE_BPS,
+            window_ticks: CIRCUIT_BREAKER_WINDOW_TICKS,
+            cooldown_ticks: CIRCUIT_BREAKER_COOLDOWN_TICKS,
+        }
+    }
+}
+
+impl BreakerLimits {
+    pub fn new(move_bps: u64, window_ticks: u64, cooldown_ticks: u64) -> Result<Self, u32> {
+        if move_bps == 0 || move_bps > BASIS_POINTS_PRECISION || window_ticks == 0 || cooldown_ticks == 0 {
+            return Err(ERROR_INVALID_BREAKER_LIMITS);
+        }
+        Ok(BreakerLimits { move_bps, window_ticks, cooldown_ticks })
+    }
+}
+
+/// Per-market circuit breaker on every outcome price.
+/// The tick snapshots the prices at the start of each window as the reference; a trade that
+/// moves any price further than `limits.move_bps` from it halts trading for the cool-down.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct CircuitBreaker {
+    pub limits: BreakerLimits,
+    pub window_start: u64,
+    pub reference_prices: Vec<u64>, // per outcome, empty until the next tick opens a window
+    pub halted_until: u64,
+}
+
+impl CircuitBreaker {
+    pub fn is_halted(&self, counter: u64) -> bool {
+        counter < self.halted_until
+    }
+
+    // Snapshot the prices on tick if the previous window has run out; returns whether it rolled
+    pub fn roll(&mut self, prices: Vec<u64>, counter: u64) -> bool {
+        if !self.reference_prices.is_empty() && counter < self.window_start.saturating_add(self.limits.window_ticks) {
+            return false;
+        }
+        self.window_start = counter;
+        self.reference_prices = prices;
+        true
+    }
+
+    // Halt if any price after a trade moved too far within the window; returns whether it tripped
+    pub fn trip(&mut self, prices: &[u64], counter: u64) -> Result<bool, u32> {
+        let max_move = self.limits.move_bps * BASIS_POINTS_TO_PRICE;
+        let moved = self.reference_prices.iter().zip(prices)
+            .any(|(reference, price)| price.abs_diff(*reference) > max_move);
+        if !moved {
+            return Ok(false);
+        }
+        self.halted_until = safe_add(counter, self.limits.cooldown_ticks)?;
+        // Trading resumes against a fresh window
+        self.reference_prices.clear();
+        Ok(true)
+    }
+}
+
+// Halt and resume reported in CIRCUIT_BREAKER events
+pub const BREAKER_RESUMED: u64 = 0;
+pub const BREAKER_HALTED: u64 = 1;
+
+pub fn emit_breaker_event(market: &Market, action: u64, counter: u64) {
+    let prices = market.data.prices().unwrap_or_default();
+    let mut data = vec![
+        market.market_id,
+        action,
+        market.data.breaker.halted_until,
+        counter,
+        prices.len() as u64,
+    ];
+    data.extend_from_slice(&prices);
+    insert_event(EVENT_CIRCUIT_BREAKER, &mut data);
+}
+
+/// Record a halt so the tick reports the market once it can trade again.
+pub fn halt_market(market: &Market, counter: u64) {
+    GLOBAL_STATE.0.borrow_mut().track_halted_market(market.market_id);
+    emit_breaker_event(market, BREAKER_HALTED, counter);
+}
+
+/// Emit the resume event of every market whose cool-down ended; called once per tick.
+pub fn resume_halted_markets(counter: u64) {
+    let market_ids = GLOBAL_STATE.0.borrow().halted_markets.clone();
+    for market_id in market_ids {
+        let market = match Market::get(market_id) {
+            Some(market) => market,
+            None => continue,
+        };
+        if !market.data.breaker.is_halted(counter) {
+            GLOBAL_STATE.0.borrow_mut().untrack_halted_market(market_id);
+            emit_breaker_event(&market, BREAKER_RESUMED, counter);
+        }
+    }
+}
+
+impl StorageData for CircuitBreaker {
+    fn from_data(u64data: &mut std::slice::IterMut<u64>) -> Self {
+        CircuitBreaker {
+            limits: BreakerLimits {
+                move_bps: *u64data.next().unwrap(),
+                window_ticks: *u64data.next().unwrap(),
+                cooldown_ticks: *u64data.next().unwrap(),
+            },
+            window_start: *u64data.next().unwrap(),
+            reference_prices: {
+                let count = *u64data.next().unwrap();
+                (0..count).map(|_| *u64data.next().unwrap()).collect()
+            },
+            halted_until: *u64data.next().unwrap(),
+        }
+    }
+
+    fn to_data(&self, data: &mut Vec<u64>) {
+        data.push(self.limits.move_bps);
+        data.push(self.limits.window_ticks);
+        data.push(self.limits.cooldown_ticks);
+        data.push(self.window_start);
+        data.push(self.reference_prices.len() as u64);
+        data.extend_from_slice(&self.reference_prices);
+        data.push(self.halted_until);
+    }
+}
@@ -1,5 +1,16 @@
+use zkwasm_rest_abi::StorageData;
 use crate::error::*;
-use crate::event::{insert_event, EVENT_BET_UPDATE};
+use crate::event::{insert_event, MarketEvent, EVENT_BALANCE_TRANSFER, EVENT_BET_UPDATE, EVENT_LIQUIDITY_UPDATE, EVENT_MARKET_CREATED, EVENT_MARKET_STATUS, EVENT_MARKET_UPDATE, EVENT_PAUSE_UPDATE, EVENT_SET_UPDATE, EVENT_SHARE_TRANSFER};
+use crate::circuit_breaker::{halt_market, BreakerLimits};
+use crate::config::{PRICE_PRECISION, RESOLUTION_BOND};
+use crate::dispute::DisputeBond;
+use crate::fee::{FeeSchedule, FeeSplit};
+use crate::market::{Market, MarketData, MarketStatus, MarketText, Pause, ScalarRange, ALL_MARKETS, OUTCOME_INVALID};
+use crate::math_safe::{calculate_fee_at_rate, safe_add, validate_bet_amount};
+use crate::order::{emit_order_event, LimitOrder, OrderBook, ORDER_CANCELLED, ORDER_PLACED};
+use crate::pricing::PricingModel;
+use crate::proposal::{emit_proposal_event, ResolutionProposal, PROPOSAL_CHALLENGED, PROPOSAL_PROPOSED, PROPOSAL_SETTLED};
+use crate::resolver::{emit_vote_event, ResolverCommittee};
 use crate::player::Player;
 use crate::state::{GLOBAL_STATE};
 
@@ -10,6 +21,8 @@ pub enum Command {
     // Standard withdraw and deposit
     Withdraw(Withdraw),
     Deposit(Deposit),
+    // In-rollup balance transfer between players
+    Transfer(Transfer),
     // Standard player install and timer
     InstallPlayer,
     Tick,
@@ -66,14 +79,86 @@ impl CommandHandler for Deposit {
     }
 }
 
+#[derive(Clone)]
+pub struct Transfer {
+    pub to: [u64; 2],
+    pub amount: u64,
+}
+
+impl CommandHandler for Transfer {
+    fn handle(&self, pid: &[u64; 2], nonce: u64, _rand: &[u64; 4], counter: u64) -> Result<(), u32> {
+        let mut player = Player::get_from_pid(pid);
+        match player.as_mut() {
+            None => Err(ERROR_PLAYER_NOT_EXIST),
+            Some(player) => {
+                player.check_and_inc_nonce(nonce);
+                if self.amount == 0 || self.to == *pid {
+                    return Err(ERROR_INVALID_TRANSFER);
+                }
+                let mut recipient = Player::get_from_pid(&self.to).ok_or(ERROR_PLAYER_NOT_EXIST)?;
+                player.data.spend_balance(self.amount)?;
+                recipient.data.credit_balance(self.amount)?;
+                recipient.store();
+                player.store();
+
+                let mut data = vec![pid[0], pid[1], self.to[0], self.to[1], self.amount, counter];
+                insert_event(EVENT_BALANCE_TRANSFER, &mut data);
+                Ok(())
+            }
+        }
+    }
+}
+
 #[derive(Clone)]
 pub enum Activity {
     // Prediction market activities
-    Bet(u64, u64),  // bet_type, amount
-    Sell(u64, u64), // sell_type, shares_amount
-    Resolve(u64),   // outcome
-    Claim,          // claim winnings
-    WithdrawFees,   // withdraw collected fees (admin only)
+    Bet(u64, u64, u64, u64),  // market_id, bet_type (outcome index), amount, min_shares_out
+    Sell(u64, u64, u64, u64), // market_id, sell_type (outcome index), shares_amount, min_payout
+    Resolve(u64, u64),   // market_id, winning outcome index or OUTCOME_INVALID
+    Claim(u64),          // market_id, claim winnings
+    WithdrawFees(u64),   // market_id, withdraw collected fees (admin only)
+    CreateMarket(CreateMarket), // create a new market (admin only)
+    BuyShares(u64, u64, u64, u64), // market_id, bet_type (outcome index), shares, max_cost
+    PlaceOrder(u64, u64, u64, u64), // market_id, outcome index, limit_price, amount
+    CancelOrder(u64, u64),          // market_id, order_id
+    AddLiquidity(u64, u64),         // market_id, collateral amount
+    RemoveLiquidity(u64, u64),      // market_id, lp_shares
+    CancelMarket(u64),              // market_id, void a market before it starts (admin only)
+    Dispute(u64),                   // market_id, challenge the proposed outcome (bonded if proposed by a player)
+    SetResolvers(u64, u64, Vec<[u64; 2]>), // market_id, threshold, member player ids (admin only)
+    VoteResolution(u64, u64),       // market_id, outcome voted by a committee member
+    ProposeOutcome(u64, u64),       // market_id, outcome backed by a resolution bond
+    SettleProposal(u64),            // market_id, pay out resolution bonds once final
+    Pause(u64, bool),               // market_id or ALL_MARKETS, also halt claims (admin only)
+    Unpause(u64),                   // market_id or ALL_MARKETS (admin only)
+    SetCircuitBreaker(u64, u64, u64, u64), // market_id, move_bps, window_ticks, cooldown_ticks (admin only)
+    MintSet(u64, u64),              // market_id, collateral turned into one share of every outcome per unit
+    RedeemSet(u64, u64),            // market_id, complete sets burned for collateral
+    TransferShares(u64, [u64; 2], u64, u64), // market_id, recipient player id, outcome index, shares
+    SetFeeSchedule(u64, u64, u64, u64, u64), // market_id, base_bps, edge_bps, ramp_ticks, ramp_bps (admin only)
+    SetFeeSplit(u64, [u64; 2], [u64; 4]), // market_id, creator player id, platform/creator/LP/referrer weights (admin only)
+    WithdrawCreatorFees(u64),       // market_id, withdraw the creator share of fees (creator only)
+    SetReferrer([u64; 2]),          // referrer player id, set once
+    WithdrawReferralFees,           // move accrued referral rebates to the balance
+}
+
+// Liquidity action reported in LIQUIDITY_UPDATE events
+pub const LIQUIDITY_ADDED: u64 = 0;
+pub const LIQUIDITY_REMOVED: u64 = 1;
+
+// Complete set action reported in SET_UPDATE events
+pub const SET_MINTED: u64 = 0;
+pub const SET_REDEEMED: u64 = 1;
+
+#[derive(Clone)]
+pub struct CreateMarket {
+    pub start_time: u64,
+    pub end_time: u64,
+    pub resolution_time: u64,
+    pub pricing: PricingModel,
+    pub liquidity: Vec<u64>, // initial pricing state per outcome, [NO, YES] for binary markets
+    pub text: MarketText,
+    pub scalar: Option<ScalarRange>, // bounds of a scalar market, whose outcomes are [SHORT, LONG]
 }
 
 impl CommandHandler for Activity {
@@ -84,22 +169,103 @@ impl CommandHandler for Activity {
             Some(player) => {
                 player.check_and_inc_nonce(nonce);
                 match self {
-                    Activity::Bet(bet_type, amount) => {
-                        Self::handle_bet(player, *bet_type, *amount, counter)
+                    Activity::Bet(market_id, bet_type, amount, min_shares_out) => {
+                        Self::handle_bet(player, *market_id, *bet_type, *amount, *min_shares_out, counter)
                     },
-                    Activity::Sell(sell_type, shares) => {
-                        Self::handle_sell(player, *sell_type, *shares, counter)
+                    Activity::Sell(market_id, sell_type, shares, min_payout) => {
+                        Self::handle_sell(player, *market_id, *sell_type, *shares, *min_payout, counter)
                     },
-                    Activity::Resolve(outcome) => {
+                    Activity::Resolve(market_id, outcome) => {
                         // Only admin can resolve - we need to check this at a higher level
-                        Self::handle_resolve(*outcome, counter)
+                        Self::handle_resolve(player, *market_id, *outcome, counter)
                     },
-                    Activity::Claim => {
-                        Self::handle_claim(player, counter)
+                    Activity::Claim(market_id) => {
+                        Self::handle_claim(player, *market_id, counter)
                     },
-                    Activity::WithdrawFees => {
+                    Activity::WithdrawFees(market_id) => {
                         // Only admin can withdraw fees - we need to check this at a higher level
-                        Self::handle_withdraw_fees(player, counter)
+                        Self::handle_withdraw_fees(player, *market_id, counter)
+                    },
+                    Activity::CreateMarket(params) => {
+                        // Only admin can create markets - we need to check this at a higher level
+                        Self::handle_create_market(player, params, counter)
+                    },
+                    Activity::BuyShares(market_id, bet_type, shares, max_cost) => {
+                        Self::handle_buy_shares(player, *market_id, *bet_type, *shares, *max_cost, counter)
+                    },
+                    Activity::PlaceOrder(market_id, outcome, limit_price, amount) => {
+                        Self::handle_place_order(player, *market_id, *outcome, *limit_price, *amount)
+                    },
+                    Activity::CancelOrder(market_id, order_id) => {
+                        Self::handle_cancel_order(player, *market_id, *order_id, counter)
+                    },
+                    Activity::AddLiquidity(market_id, amount) => {
+                        Self::handle_add_liquidity(player, *market_id, *amount, counter)
+                    },
+                    Activity::RemoveLiquidity(market_id, lp_shares) => {
+                        Self::handle_remove_liquidity(player, *market_id, *lp_shares, counter)
+                    },
+                    Activity::CancelMarket(market_id) => {
+                        // Only admin can cancel markets - we need to check this at a higher level
+                        Self::handle_cancel_market(player, *market_id, counter)
+                    },
+                    Activity::Dispute(market_id) => {
+                        Self::handle_dispute(player, *market_id, counter)
+                    },
+                    Activity::SetResolvers(market_id, threshold, members) => {
+                        // Only admin can appoint resolvers - we need to check this at a higher level
+                        Self::handle_set_resolvers(player, *market_id, *threshold, members, counter)
+                    },
+                    Activity::VoteResolution(market_id, outcome) => {
+                        Self::handle_vote_resolution(player, *market_id, *outcome, counter)
+                    },
+                    Activity::ProposeOutcome(market_id, outcome) => {
+                        Self::handle_propose_outcome(player, *market_id, *outcome, counter)
+                    },
+                    Activity::SettleProposal(market_id) => {
+                        Self::handle_settle_proposal(player, *market_id, counter)
+                    },
+                    Activity::Pause(market_id, halt_claims) => {
+                        // Only admin can pause - we need to check this at a higher level
+                        let pause = Pause { trading: true, claims: *halt_claims };
+                        Self::handle_set_pause(player, *market_id, pause, counter)
+                    },
+                    Activity::Unpause(market_id) => {
+                        // Only admin can unpause - we need to check this at a higher level
+                        Self::handle_set_pause(player, *market_id, Pause::default(), counter)
+                    },
+                    Activity::SetCircuitBreaker(market_id, move_bps, window_ticks, cooldown_ticks) => {
+                        // Only admin can set the circuit breaker - we need to check this at a higher level
+                        let limits = BreakerLimits::new(*move_bps, *window_ticks, *cooldown_ticks)?;
+                        Self::handle_set_circuit_breaker(player, *market_id, limits, counter)
+                    },
+                    Activity::MintSet(market_id, amount) => {
+                        Self::handle_mint_set(player, *market_id, *amount, counter)
+                    },
+                    Activity::RedeemSet(market_id, amount) => {
+                        Self::handle_redeem_set(player, *market_id, *amount, counter)
+                    },
+                    Activity::TransferShares(market_id, to, outcome, shares) => {
+                        Self::handle_transfer_shares(player, *market_id, to, *outcome, *shares, counter)
+                    },
+                    Activity::SetFeeSchedule(market_id, base_bps, edge_bps, ramp_ticks, ramp_bps) => {
+                        // Only admin can set fees - we need to check this at a higher level
+                        let schedule = FeeSchedule::new(*base_bps, *edge_bps, *ramp_ticks, *ramp_bps)?;
+                        Self::handle_set_fee_schedule(player, *market_id, schedule, counter)
+                    },
+                    Activity::SetFeeSplit(market_id, creator, [platform_bps, creator_bps, lp_bps, referrer_bps]) => {
+                        // Only admin can set fees - we need to check this at a higher level
+                        let split = FeeSplit::new(*platform_bps, *creator_bps, *lp_bps, *referrer_bps)?;
+                        Self::handle_set_fee_split(player, *market_id, *creator, split, counter)
+                    },
+                    Activity::WithdrawCreatorFees(market_id) => {
+                        Self::handle_withdraw_creator_fees(player, *market_id, counter)
+                    },
+                    Activity::SetReferrer(referrer) => {
+                        Self::handle_set_referrer(player, *referrer)
+                    },
+                    Activity::WithdrawReferralFees => {
+                        Self::handle_withdraw_referral_fees(player)
                     }
                 }
             }
@@ -108,104 +274,500 @@ impl CommandHandler for Activity {
 }
 
 impl Activity {
-    fn handle_bet(player: &mut Player, bet_type: u64, amount: u64, _counter: u64) -> Result<(), u32> {
+    fn handle_bet(player: &mut Player, market_id: u64, bet_type: u64, amount: u64, min_shares_out: u64, counter: u64) -> Result<(), u32> {
         if amount == 0 {
             return Err(ERROR_INVALID_BET_AMOUNT);
         }
 
+        let (mut market, stored) = Self::load_market(market_id)?;
+
         // Check if market is active
-        let current_time = GLOBAL_STATE.0.borrow_mut().ensure_active()?;
+        let current_time = GLOBAL_STATE.0.borrow().ensure_active(&market.data)?;
         let txid = GLOBAL_STATE.0.borrow().txcounter;
 
         // Check player balance
         player.data.spend_balance(amount)?;
 
-        // Place bet using unified function
-        let shares = GLOBAL_STATE.0.borrow_mut().market.place_bet(bet_type, amount)?;
-        if bet_type == 1 {
-            player.data.add_yes_shares(shares);
-        } else {
-            player.data.add_no_shares(shares);
+        // Place bet using unified function, at the fee rate of the player's volume tier
+        let fee_tier = player.data.fee_tier();
+        let fee_bps = market.data.trade_fee_bps(bet_type, current_time, fee_tier)?;
+        market.data.quote_bet(bet_type, amount, fee_bps, min_shares_out)?;
+        let shares = market.data.place_bet(bet_type, amount, fee_bps)?;
+        let fee = calculate_fee_at_rate(amount, fee_bps)?;
+        player.data.record_trade(amount, fee)?;
+        player.data.add_shares(market_id, bet_type, shares);
+        player.data.check_position_limit(market_id, bet_type)?;
+        market.data.check_open_interest()?;
+        player.data.add_outcome_cost_basis(market_id, bet_type, amount);
+        market.data.add_cost_basis(amount)?;
+        // All fallible work first: the referrer is stored right away and a halt is tracked globally
+        let halted = market.data.check_circuit_breaker(current_time)?;
+        Self::pay_referrer(player, &mut market.data, fee)?;
+        if halted {
+            halt_market(&market, current_time);
         }
 
         // Store updated data
+        Self::store_market(&market, stored, counter);
         player.store();
 
         // Emit events
         // Self::emit_player_event(&player);
-        Self::emit_bet_event(player.player_id, bet_type, amount, shares, txid, current_time);
+        Self::emit_market_event(&market, current_time);
+        Self::emit_bet_event(player.player_id, market_id, bet_type, amount, shares, txid, current_time, fee, fee_tier);
         Ok(())
     }
 
-    fn handle_sell(player: &mut Player, sell_type: u64, shares: u64, _counter: u64) -> Result<(), u32> {
+    fn handle_buy_shares(player: &mut Player, market_id: u64, bet_type: u64, shares: u64, max_cost: u64, counter: u64) -> Result<(), u32> {
         if shares == 0 {
             return Err(ERROR_INVALID_BET_AMOUNT);
         }
 
+        let (mut market, stored) = Self::load_market(market_id)?;
+
         // Check if market is active
-        let current_time = GLOBAL_STATE.0.borrow_mut().ensure_active()?;
+        let current_time = GLOBAL_STATE.0.borrow().ensure_active(&market.data)?;
         let txid = GLOBAL_STATE.0.borrow().txcounter;
 
-        // Check player has enough shares
-        if sell_type == 1 && player.data.yes_shares < shares {
-            return Err(ERROR_INSUFFICIENT_BALANCE);
+        // Quote the exact cost at the player's fee tier and charge it
+        let fee_tier = player.data.fee_tier();
+        let fee_bps = market.data.trade_fee_bps(bet_type, current_time, fee_tier)?;
+        let (cost, fee) = market.data.quote_cost(bet_type, shares, fee_bps, max_cost)?;
+        player.data.spend_balance(cost)?;
+        player.data.record_trade(cost, fee)?;
+
+        market.data.buy_shares(bet_type, shares, fee_bps)?;
+        player.data.add_shares(market_id, bet_type, shares);
+        player.data.check_position_limit(market_id, bet_type)?;
+        market.data.check_open_interest()?;
+        player.data.add_outcome_cost_basis(market_id, bet_type, cost);
+        market.data.add_cost_basis(cost)?;
+        // All fallible work first: the referrer is stored right away and a halt is tracked globally
+        let halted = market.data.check_circuit_breaker(current_time)?;
+        Self::pay_referrer(player, &mut market.data, fee)?;
+        if halted {
+            halt_market(&market, current_time);
         }
-        if sell_type != 1 && player.data.no_shares < shares {
-            return Err(ERROR_INSUFFICIENT_BALANCE);
+
+        // Store updated data
+        Self::store_market(&market, stored, counter);
+        player.store();
+
+        // Emit events
+        Self::emit_market_event(&market, current_time);
+        Self::emit_bet_event(player.player_id, market_id, bet_type, cost, shares, txid, current_time, fee, fee_tier);
+        Ok(())
+    }
+
+    fn handle_place_order(player: &mut Player, market_id: u64, outcome: u64, limit_price: u64, amount: u64) -> Result<(), u32> {
+        validate_bet_amount(amount)?;
+        if limit_price == 0 || limit_price >= PRICE_PRECISION {
+            return Err(ERROR_INVALID_LIMIT_PRICE);
         }
 
-        // Sell shares using unified function
-        let payout = GLOBAL_STATE.0.borrow_mut().market.sell_shares(sell_type, shares)?;
-        
-        // Update player shares
-        if sell_type == 1 {
-            player.data.yes_shares -= shares;
-        } else {
-            player.data.no_shares -= shares;
+        let (market, _) = Self::load_market(market_id)?;
+        let current_time = GLOBAL_STATE.0.borrow().ensure_active(&market.data)?;
+        market.data.validate_outcome(outcome)?;
+
+        let mut book = OrderBook::get(market_id);
+        if book.is_full() {
+            return Err(ERROR_ORDER_BOOK_FULL);
+        }
+
+        // Lock the collateral until the order fills or is cancelled
+        player.data.lock_balance(amount)?;
+
+        let order = LimitOrder {
+            order_id: GLOBAL_STATE.0.borrow_mut().next_order_id(),
+            pid: player.player_id,
+            outcome,
+            limit_price,
+            amount,
+        };
+        book.orders.push(order.clone());
+        book.store();
+        GLOBAL_STATE.0.borrow_mut().track_order_market(market_id);
+        player.store();
+
+        emit_order_event(&order, market_id, ORDER_PLACED, 0, 0, current_time);
+        Ok(())
+    }
+
+    fn handle_cancel_order(player: &mut Player, market_id: u64, order_id: u64, counter: u64) -> Result<(), u32> {
+        let mut book = OrderBook::get(market_id);
+        let order = book.remove(order_id, &player.player_id)?;
+        player.data.unlock_balance(order.amount);
+
+        book.store();
+        if book.orders.is_empty() {
+            GLOBAL_STATE.0.borrow_mut().untrack_order_market(market_id);
         }
+        player.store();
+
+        emit_order_event(&order, market_id, ORDER_CANCELLED, 0, 0, counter);
+        Ok(())
+    }
+
+    fn handle_add_liquidity(player: &mut Player, market_id: u64, amount: u64, counter: u64) -> Result<(), u32> {
+        let (mut market, stored) = Self::load_market(market_id)?;
+        let current_time = GLOBAL_STATE.0.borrow().ensure_active(&market.data)?;
+
+        // Deposit real collateral as complete sets in the prize pool and deepen the AMM at the current prices
+        player.data.spend_balance(amount)?;
+        let lp_shares = market.data.add_liquidity(amount)?;
+        player.data.add_lp_shares(market_id, lp_shares);
+
+        Self::store_market(&market, stored, counter);
+        player.store();
+
+        Self::emit_market_event(&market, current_time);
+        Self::emit_liquidity_event(player.player_id, market_id, LIQUIDITY_ADDED, amount, lp_shares, current_time);
+        Ok(())
+    }
+
+    fn handle_mint_set(player: &mut Player, market_id: u64, amount: u64, counter: u64) -> Result<(), u32> {
+        let (mut market, stored) = Self::load_market(market_id)?;
+        let current_time = GLOBAL_STATE.0.borrow().ensure_active(&market.data)?;
+
+        // One share of every outcome per unit of collateral, outside the AMM, so the sets
+        // can only be redeemed as a whole and never sold leg by leg
+        player.data.spend_balance(amount)?;
+        market.data.mint_set(amount)?;
+        player.data.add_set_shares(market_id, market.data.outcome_count(), amount);
+        for outcome in 0..market.data.outcome_count() {
+            player.data.check_position_limit(market_id, outcome)?;
+        }
+        market.data.check_open_interest()?;
+        player.data.add_cost_basis(market_id, amount);
+        market.data.add_cost_basis(amount)?;
+
+        Self::store_market(&market, stored, counter);
+        player.store();
+
+        Self::emit_set_event(player.player_id, market_id, SET_MINTED, amount, current_time);
+        Ok(())
+    }
+
+    fn handle_redeem_set(player: &mut Player, market_id: u64, amount: u64, counter: u64) -> Result<(), u32> {
+        let (mut market, stored) = Self::load_market(market_id)?;
+        let current_time = GLOBAL_STATE.0.borrow().ensure_active(&market.data)?;
+
+        // Burn one share of every outcome per unit of collateral returned
+        player.data.spend_set_shares(market_id, market.data.outcome_count(), amount)?;
+        market.data.redeem_set(amount)?;
+        player.data.add_balance(amount);
+        let reduced = player.data.reduce_cost_basis(market_id, amount);
+        market.data.remove_cost_basis(reduced)?;
+
+        Self::store_market(&market, stored, counter);
+        player.store();
+
+        Self::emit_set_event(player.player_id, market_id, SET_REDEEMED, amount, current_time);
+        Ok(())
+    }
+
+    fn handle_transfer_shares(player: &mut Player, market_id: u64, to: &[u64; 2], outcome: u64, shares: u64, counter: u64) -> Result<(), u32> {
+        let (market, _) = Self::load_market(market_id)?;
+        // Once claims open a transfer could pay the same shares twice
+        if market.data.is_finalized(counter) {
+            return Err(ERROR_MARKET_ALREADY_RESOLVED);
+        }
+        market.data.validate_outcome(outcome)?;
+        if shares == 0 || *to == player.player_id {
+            return Err(ERROR_INVALID_TRANSFER);
+        }
+        let mut recipient = Player::get_from_pid(to).ok_or(ERROR_PLAYER_NOT_EXIST)?;
+
+        // The outcome's cost basis moves pro rata, so invalid refunds follow the shares
+        let cost_basis = player.data.transfer_shares_out(market_id, outcome, shares)?;
+        recipient.data.add_shares(market_id, outcome, shares);
+        recipient.data.check_position_limit(market_id, outcome)?;
+        recipient.data.add_outcome_cost_basis(market_id, outcome, cost_basis);
+
+        recipient.store();
+        player.store();
+
+        Self::emit_transfer_event(player.player_id, *to, market_id, outcome, shares, counter);
+        Ok(())
+    }
+
+    fn handle_remove_liquidity(player: &mut Player, market_id: u64, lp_shares: u64, counter: u64) -> Result<(), u32> {
+        let (mut market, stored) = Self::load_market(market_id)?;
+
+        // Redeemed at par while trading, settled with the outcome once final, and locked in between
+        player.data.spend_lp_shares(market_id, lp_shares)?;
+        let payout = market.data.remove_liquidity(lp_shares, counter)?;
+        player.data.add_balance(payout);
+
+        Self::store_market(&market, stored, counter);
+        player.store();
+
+        Self::emit_market_event(&market, counter);
+        Self::emit_liquidity_event(player.player_id, market_id, LIQUIDITY_REMOVED, payout, lp_shares, counter);
+        Ok(())
+    }
+
+    fn handle_sell(player: &mut Player, market_id: u64, sell_type: u64, shares: u64, min_payout: u64, counter: u64) -> Result<(), u32> {
+        if shares == 0 {
+            return Err(ERROR_INVALID_BET_AMOUNT);
+        }
+
+        let (mut market, stored) = Self::load_market(market_id)?;
+
+        // Check if market is active
+        let current_time = GLOBAL_STATE.0.borrow().ensure_active(&market.data)?;
+        let txid = GLOBAL_STATE.0.borrow().txcounter;
+
+        // Check player has enough shares and take them out of the position
+        market.data.validate_outcome(sell_type)?;
+        player.data.spend_shares(market_id, sell_type, shares)?;
+
+        // Sell shares using unified function, at the fee rate of the player's volume tier
+        let fee_tier = player.data.fee_tier();
+        let fee_bps = market.data.trade_fee_bps(sell_type, current_time, fee_tier)?;
+        let (_, fee) = market.data.quote_sale(sell_type, shares, fee_bps, min_payout)?;
+        let payout = market.data.sell_shares(sell_type, shares, fee_bps)?;
+        player.data.record_trade(payout + fee, fee)?;
 
         // Add payout to player balance
         player.data.balance += payout;
+        let reduced = player.data.reduce_outcome_cost_basis(market_id, sell_type, payout);
+        market.data.remove_cost_basis(reduced)?;
+        // All fallible work first: the referrer is stored right away and a halt is tracked globally
+        let halted = market.data.check_circuit_breaker(current_time)?;
+        Self::pay_referrer(player, &mut market.data, fee)?;
+        if halted {
+            halt_market(&market, current_time);
+        }
 
         // Store updated data
+        Self::store_market(&market, stored, counter);
         player.store();
 
         // Emit events
         // Self::emit_player_event(&player);
-        Self::emit_sell_event(player.player_id, sell_type, shares, payout, txid, current_time);
+        Self::emit_market_event(&market, current_time);
+        Self::emit_sell_event(player.player_id, market_id, sell_type, shares, payout, txid, current_time, fee, fee_tier);
+
+        Ok(())
+    }
+
+    fn handle_resolve(player: &mut Player, market_id: u64, outcome: u64, counter: u64) -> Result<(), u32> {
+        let (mut market, stored) = Self::load_market(market_id)?;
+        // Markets with a resolver committee are resolved by its votes only
+        if ResolverCommittee::get(market_id).is_some() {
+            return Err(ERROR_UNAUTHORIZED);
+        }
+
+        // Proposed outcomes only become claimable after the dispute window
+        market.data.propose_resolution(outcome, counter)?;
+        Self::settle_dispute_bond(player, &mut market)?;
+
+        Self::store_market(&market, stored, counter);
+        // Persist the admin nonce
+        player.store();
+        Ok(())
+    }
+
+    fn handle_cancel_market(player: &mut Player, market_id: u64, counter: u64) -> Result<(), u32> {
+        let (mut market, stored) = Self::load_market(market_id)?;
+        market.data.cancel(counter)?;
+
+        Self::store_market(&market, stored, counter);
+        // Persist the admin nonce
+        player.store();
+        Ok(())
+    }
+
+    fn handle_set_resolvers(player: &mut Player, market_id: u64, threshold: u64, members: &[[u64; 2]], counter: u64) -> Result<(), u32> {
+        let (market, _) = Self::load_market(market_id)?;
+        if market.data.is_finalized(counter) {
+            return Err(ERROR_MARKET_ALREADY_RESOLVED);
+        }
+
+        // Replacing the committee also discards any votes cast so far
+        let committee = ResolverCommittee::new(market_id, members.to_vec(), threshold)?;
+        committee.store();
+
+        // Persist the admin nonce
+        player.store();
+        Ok(())
+    }
+
+    fn handle_vote_resolution(player: &mut Player, market_id: u64, outcome: u64, counter: u64) -> Result<(), u32> {
+        let (mut market, stored) = Self::load_market(market_id)?;
+        let mut committee = ResolverCommittee::get(market_id).ok_or(ERROR_UNAUTHORIZED)?;
+        if market.data.is_finalized(counter) {
+            return Err(ERROR_MARKET_ALREADY_RESOLVED);
+        }
+        // Votes are only recorded once the market can be resolved
+        if !market.data.can_resolve(counter) {
+            return Err(ERROR_MARKET_NOT_ENDED);
+        }
+
+        // Votes count on the settled outcome, so scalar values agree after clamping
+        let outcome = market.data.settle_outcome(outcome)?.unwrap_or(OUTCOME_INVALID);
+        let votes = committee.vote(&player.player_id, outcome)?;
 
+        // Enough members agree: propose the outcome and start a fresh round
+        if votes >= committee.threshold {
+            market.data.propose_resolution(outcome, counter)?;
+            Self::settle_dispute_bond(player, &mut market)?;
+            committee.clear_votes();
+            Self::store_market(&market, stored, counter);
+        }
+        committee.store();
+        player.store();
+
+        emit_vote_event(&committee, &player.player_id, outcome, votes, counter);
         Ok(())
     }
 
-    fn handle_resolve(outcome: u64, _counter: u64) -> Result<(), u32> {
-        let mut global_state = GLOBAL_STATE.0.borrow_mut();
-        let current_time = global_state.counter;
+    fn handle_propose_outcome(player: &mut Player, market_id: u64, outcome: u64, counter: u64) -> Result<(), u32> {
+        let (mut market, stored) = Self::load_market(market_id)?;
 
-        // TODO: Uncomment this when production is ready
-        if !global_state.market.can_resolve(current_time) && false {
-             return Err(ERROR_MARKET_NOT_RESOLVED);
+        // Committee markets are resolved by their committee only
+        if ResolverCommittee::get(market_id).is_some() {
+            return Err(ERROR_UNAUTHORIZED);
+        }
+        // Bonded proposals only open a fresh resolution of a closed market
+        if market.data.is_resolved() {
+            return Err(ERROR_MARKET_ALREADY_RESOLVED);
+        }
+        if ResolutionProposal::get(market_id).is_some() {
+            return Err(ERROR_PROPOSAL_EXISTS);
+        }
+
+        // Bond the outcome; it finalizes after the dispute window unless challenged
+        player.data.spend_balance(RESOLUTION_BOND)?;
+        market.data.propose_resolution(outcome, counter)?;
+        let proposal = ResolutionProposal {
+            market_id,
+            proposer: player.player_id,
+            outcome: market.data.resolved_outcome().ok_or(ERROR_MARKET_NOT_RESOLVED)?,
+            bond: RESOLUTION_BOND,
+            challenger: None,
+        };
+
+        proposal.store();
+        Self::store_market(&market, stored, counter);
+        player.store();
+
+        emit_proposal_event(&proposal, PROPOSAL_PROPOSED, counter);
+        Ok(())
+    }
+
+    fn handle_settle_proposal(player: &mut Player, market_id: u64, counter: u64) -> Result<(), u32> {
+        let (mut market, stored) = Self::load_market(market_id)?;
+        if !market.data.is_finalized(counter) {
+            return Err(ERROR_MARKET_NOT_RESOLVED);
         }
+        let proposal = ResolutionProposal::get(market_id).ok_or(ERROR_PROPOSAL_NOT_EXIST)?;
+
+        // Anyone can settle; bonds go to the side that matches the final outcome
+        let final_outcome = market.data.resolved_outcome().ok_or(ERROR_MARKET_NOT_RESOLVED)?;
+        let (payouts, treasury_cut) = proposal.settle(final_outcome)?;
+        // The treasury's cut is withdrawn with the market's platform fees
+        market.data.total_fees_collected = safe_add(market.data.total_fees_collected, treasury_cut)?;
+
+        // Load every winner before storing any, as handlers have no rollback
+        let mut winners = vec![];
+        for (pid, amount) in payouts {
+            if pid == player.player_id {
+                player.data.add_balance(amount);
+            } else {
+                let mut winner = Player::get_from_pid(&pid).ok_or(ERROR_PLAYER_NOT_EXIST)?;
+                winner.data.add_balance(amount);
+                winners.push(winner);
+            }
+        }
+        for winner in winners {
+            winner.store();
+        }
+
+        ResolutionProposal::remove(market_id);
+        Self::store_market(&market, stored, counter);
+        player.store();
+
+        emit_proposal_event(&proposal, PROPOSAL_SETTLED, counter);
+        Ok(())
+    }
+
+    fn handle_dispute(player: &mut Player, market_id: u64, counter: u64) -> Result<(), u32> {
+        let (mut market, stored) = Self::load_market(market_id)?;
+
+        match ResolutionProposal::get(market_id) {
+            // A bonded proposal can only be challenged by matching its bond
+            Some(mut proposal) => {
+                proposal.challenge(&player.player_id)?;
+                player.data.spend_balance(proposal.bond)?;
+                market.data.dispute(counter)?;
+                proposal.store();
+                emit_proposal_event(&proposal, PROPOSAL_CHALLENGED, counter);
+            },
+            // Only players holding shares or LP shares of the market can challenge the admin's or
+            // committee's outcome, bonding it; the resolution that ends the dispute settles the bond
+            None => {
+                if !player.data.position(market_id).is_some_and(|position| position.has_stake()) {
+                    return Err(ERROR_UNAUTHORIZED);
+                }
+                let outcome = market.data.resolved_outcome().ok_or(ERROR_DISPUTE_WINDOW_CLOSED)?;
+                player.data.spend_balance(RESOLUTION_BOND)?;
+                market.data.dispute(counter)?;
+                let dispute = DisputeBond {
+                    market_id,
+                    disputer: player.player_id,
+                    outcome,
+                    bond: RESOLUTION_BOND,
+                };
+                dispute.store();
+            },
+        }
+
+        Self::store_market(&market, stored, counter);
+        player.store();
+        Ok(())
+    }
 
-        let outcome_bool = outcome != 0;
-        global_state.market.resolve(outcome_bool)?;
+    // Settle the bond of a dispute once the admin's or committee's resolution makes the outcome final
+    fn settle_dispute_bond(player: &mut Player, market: &mut Market) -> Result<(), u32> {
+        let dispute = match DisputeBond::get(market.market_id) {
+            Some(dispute) if market.data.status == MarketStatus::Finalized => dispute,
+            _ => return Ok(()),
+        };
+        let final_outcome = market.data.resolved_outcome().ok_or(ERROR_MARKET_NOT_RESOLVED)?;
+        let (refund, slashed) = dispute.settle(final_outcome);
+        market.data.total_fees_collected = safe_add(market.data.total_fees_collected, slashed)?;
+        if dispute.disputer == player.player_id {
+            player.data.add_balance(refund);
+        } else if refund > 0 {
+            let mut disputer = Player::get_from_pid(&dispute.disputer).ok_or(ERROR_PLAYER_NOT_EXIST)?;
+            disputer.data.add_balance(refund);
+            disputer.store();
+        }
+        DisputeBond::remove(market.market_id);
         Ok(())
     }
 
-    fn handle_claim(player: &mut Player, _counter: u64) -> Result<(), u32> {
-        let global_state = GLOBAL_STATE.0.borrow();
-        
-        if !global_state.market.resolved {
+    fn handle_claim(player: &mut Player, market_id: u64, counter: u64) -> Result<(), u32> {
+        let (market, _) = Self::load_market(market_id)?;
+        GLOBAL_STATE.0.borrow().ensure_claimable(&market.data)?;
+
+        // Claims wait until the outcome is past its dispute window
+        if !market.data.is_finalized(counter) {
             return Err(ERROR_MARKET_NOT_RESOLVED);
         }
 
         // Check if already claimed
-        player.data.claim_winnings()?;
+        player.data.claim_winnings(market_id)?;
 
-        // Calculate payout
-        let payout = global_state.market.calculate_payout(
-            player.data.yes_shares,
-            player.data.no_shares,
-        )?;
+        // Calculate payout, or the refund if the market was voided
+        let position = player.data.position_mut(market_id);
+        let payout = if market.data.invalid {
+            market.data.calculate_refund(position.cost_basis)?
+        } else {
+            market.data.calculate_payout(&position.shares)?
+        };
 
         if payout == 0 {
             return Err(ERROR_NO_WINNING_POSITION);
@@ -215,30 +777,108 @@ impl Activity {
         player.data.add_balance(payout);
         player.store();
 
-        drop(global_state);
+        Ok(())
+    }
+
+    // Replace the pause of one market, or of every market; withdrawals are never paused
+    fn handle_set_pause(player: &mut Player, market_id: u64, pause: Pause, counter: u64) -> Result<(), u32> {
+        if market_id == ALL_MARKETS {
+            GLOBAL_STATE.0.borrow_mut().pause = pause;
+        } else {
+            let (mut market, stored) = Self::load_market(market_id)?;
+            market.data.pause = pause;
+            Self::store_market(&market, stored, counter);
+        }
+
+        // Persist the admin nonce
+        player.store();
 
+        Self::emit_pause_event(market_id, pause, counter);
         Ok(())
     }
 
-    fn handle_withdraw_fees(player: &mut Player, _counter: u64) -> Result<(), u32> {
-        let mut global_state = GLOBAL_STATE.0.borrow_mut();
-        
-        let fees_collected = global_state.market.total_fees_collected;
-        
+    // Replace a market's circuit breaker thresholds; a halt already in progress keeps its end
+    fn handle_set_circuit_breaker(player: &mut Player, market_id: u64, limits: BreakerLimits, counter: u64) -> Result<(), u32> {
+        let (mut market, stored) = Self::load_market(market_id)?;
+        if market.data.is_resolved() {
+            return Err(ERROR_MARKET_ALREADY_RESOLVED);
+        }
+
+        market.data.breaker.limits = limits;
+        Self::store_market(&market, stored, counter);
+
+        // Persist the admin nonce
+        player.store();
+        Ok(())
+    }
+
+    // Replace a market's fee curve; trades already made keep the fee they paid
+    fn handle_set_fee_schedule(player: &mut Player, market_id: u64, schedule: FeeSchedule, counter: u64) -> Result<(), u32> {
+        let (mut market, stored) = Self::load_market(market_id)?;
+        if market.data.is_resolved() {
+            return Err(ERROR_MARKET_ALREADY_RESOLVED);
+        }
+
+        market.data.fee_schedule = schedule;
+        Self::store_market(&market, stored, counter);
+
+        // Persist the admin nonce
+        player.store();
+        Ok(())
+    }
+
+    // Replace how a market's future fees are shared, and who receives the creator share
+    fn handle_set_fee_split(player: &mut Player, market_id: u64, creator: [u64; 2], split: FeeSplit, counter: u64) -> Result<(), u32> {
+        let (mut market, stored) = Self::load_market(market_id)?;
+        if market.data.is_resolved() {
+            return Err(ERROR_MARKET_ALREADY_RESOLVED);
+        }
+
+        market.data.fee_split = split;
+        market.data.creator = creator;
+        Self::store_market(&market, stored, counter);
+
+        // Persist the admin nonce
+        player.store();
+        Ok(())
+    }
+
+    fn handle_withdraw_creator_fees(player: &mut Player, market_id: u64, counter: u64) -> Result<(), u32> {
+        let (mut market, stored) = Self::load_market(market_id)?;
+        if player.player_id != market.data.creator {
+            return Err(ERROR_UNAUTHORIZED);
+        }
+
+        let creator_fees = market.data.creator_fees;
+        if creator_fees == 0 {
+            return Err(ERROR_NO_FEES_TO_WITHDRAW);
+        }
+
+        player.data.credit_balance(creator_fees)?;
+        market.data.creator_fees = 0;
+
+        Self::store_market(&market, stored, counter);
+        player.store();
+        Ok(())
+    }
+
+    fn handle_withdraw_fees(player: &mut Player, market_id: u64, counter: u64) -> Result<(), u32> {
+        let (mut market, stored) = Self::load_market(market_id)?;
+
+        let fees_collected = market.data.total_fees_collected;
+
         if fees_collected == 0 {
-            return Err(ERROR_NO_WINNING_POSITION); // Reuse this error for "no fees to withdraw"
+            return Err(ERROR_NO_FEES_TO_WITHDRAW);
         }
 
         // Transfer fees to admin's balance
         player.data.add_balance(fees_collected);
-        
-        // Reset collected fees to zero
-        global_state.market.total_fees_collected = 0;
 
-        // Release the borrow before emitting events
-        drop(global_state);
+        // Reset collected fees to zero
+        market.data.total_fees_collected = 0;
 
-        // Store updated player data
+        // Store updated market and player data
+        Self::store_market(&market, stored, counter);
         player.store();
 
         // Emit events
@@ -246,28 +886,197 @@ impl Activity {
         Ok(())
     }
 
-    fn emit_bet_event(player_id: [u64; 2], bet_type: u64, amount: u64, shares: u64, txid: u64, counter: u64) {
+    fn handle_create_market(player: &mut Player, params: &CreateMarket, counter: u64) -> Result<(), u32> {
+        // A market that has already ended could never take a bet
+        if params.end_time <= counter {
+            return Err(ERROR_INVALID_MARKET_TIME);
+        }
+
+        let data = match params.scalar {
+            Some(range) => MarketData::with_scalar_range(
+                params.text.clone(),
+                params.start_time,
+                params.end_time,
+                params.resolution_time,
+                params.pricing,
+                params.liquidity.clone(),
+                range,
+            )?,
+            None => MarketData::with_pricing(
+                params.text.clone(),
+                params.start_time,
+                params.end_time,
+                params.resolution_time,
+                params.pricing,
+                params.liquidity.clone(),
+            )?,
+        };
+        let market = GLOBAL_STATE.0.borrow_mut().add_market(MarketData { creator: player.player_id, ..data });
+
+        // Persist the admin nonce
+        player.store();
+
+        Self::emit_market_created_event(&market, counter);
+        Self::emit_status_event(&market, counter);
+        Self::emit_market_event(&market, counter);
+        Ok(())
+    }
+
+    // Move the referrer share of a trade's fee from the platform to the trader's referrer, if any
+    pub(crate) fn pay_referrer(player: &Player, market: &mut MarketData, fee: u64) -> Result<(), u32> {
+        let referrer = player.data.referrer.and_then(|pid| Player::get_from_pid(&pid));
+        let rebate = market.settle_referrer_share(fee, referrer.is_some())?;
+        if let Some(mut referrer) = referrer.filter(|_| rebate > 0) {
+            referrer.data.add_referral_earnings(rebate)?;
+            referrer.store();
+        }
+        Ok(())
+    }
+
+    fn handle_withdraw_referral_fees(player: &mut Player) -> Result<(), u32> {
+        player.data.withdraw_referral_fees()?;
+        player.store();
+        Ok(())
+    }
+
+    fn handle_set_referrer(player: &mut Player, referrer: [u64; 2]) -> Result<(), u32> {
+        let mut referrer_player = Player::get_from_pid(&referrer).ok_or(ERROR_PLAYER_NOT_EXIST)?;
+        player.data.set_referrer(&player.player_id, referrer)?;
+        referrer_player.data.referral_count += 1;
+
+        referrer_player.store();
+        player.store();
+        Ok(())
+    }
+
+    // Halt trading once a trade has moved any outcome price too far within the window
+    pub(crate) fn check_circuit_breaker(market: &mut Market, counter: u64) -> Result<(), u32> {
+        if market.data.check_circuit_breaker(counter)? {
+            halt_market(market, counter);
+        }
+        Ok(())
+    }
+
+    // Load a market along with its stored status; the tick keeps time-driven transitions stored
+    fn load_market(market_id: u64) -> Result<(Market, MarketStatus), u32> {
+        let market = Market::get(market_id).ok_or(ERROR_MARKET_NOT_EXIST)?;
+        let stored = market.data.status;
+        Ok((market, stored))
+    }
+
+    // Store a market, emitting a status event if a command moved it since it was loaded
+    fn store_market(market: &Market, stored: MarketStatus, counter: u64) {
+        market.store();
+        if market.data.status != stored {
+            Self::emit_status_event(market, counter);
+        }
+    }
+
+    pub(crate) fn emit_status_event(market: &Market, counter: u64) {
+        let mut data = vec![market.market_id, market.data.status.code(), counter];
+        insert_event(EVENT_MARKET_STATUS, &mut data);
+    }
+
+    fn emit_market_created_event(market: &Market, counter: u64) {
+        let mut data = vec![
+            market.market_id,
+            market.data.start_time,
+            market.data.end_time,
+            market.data.resolution_time,
+            counter,
+        ];
+        market.data.pricing.to_data(&mut data);
+        data.push(market.data.outcome_count());
+        data.extend_from_slice(&market.data.liquidity);
+        insert_event(EVENT_MARKET_CREATED, &mut data);
+    }
+
+    pub(crate) fn emit_market_event(market: &Market, counter: u64) {
+        let market_event = MarketEvent::from(&market.data);
+        let mut data = vec![market.market_id, counter];
+        market_event.to_data(&mut data);
+        insert_event(EVENT_MARKET_UPDATE, &mut data);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn emit_bet_event(player_id: [u64; 2], market_id: u64, bet_type: u64, amount: u64, shares: u64, txid: u64, counter: u64, fee: u64, fee_tier: u64) {
         let mut data = vec![
             txid,
+            market_id,
             player_id[0],
             player_id[1],
             bet_type,
             amount,
             shares,
             counter,
+            fee,
+            fee_tier,
         ];
         insert_event(EVENT_BET_UPDATE, &mut data);
     }
 
-    fn emit_sell_event(player_id: [u64; 2], sell_type: u64, shares: u64, payout: u64, txid: u64, counter: u64) {
+    fn emit_liquidity_event(player_id: [u64; 2], market_id: u64, action: u64, amount: u64, lp_shares: u64, counter: u64) {
+        let mut data = vec![
+            market_id,
+            player_id[0],
+            player_id[1],
+            action,
+            amount,
+            lp_shares,
+            counter,
+        ];
+        insert_event(EVENT_LIQUIDITY_UPDATE, &mut data);
+    }
+
+    fn emit_transfer_event(from: [u64; 2], to: [u64; 2], market_id: u64, outcome: u64, shares: u64, counter: u64) {
+        let mut data = vec![
+            market_id,
+            from[0],
+            from[1],
+            to[0],
+            to[1],
+            outcome,
+            shares,
+            counter,
+        ];
+        insert_event(EVENT_SHARE_TRANSFER, &mut data);
+    }
+
+    fn emit_set_event(player_id: [u64; 2], market_id: u64, action: u64, amount: u64, counter: u64) {
+        let mut data = vec![
+            market_id,
+            player_id[0],
+            player_id[1],
+            action,
+            amount,
+            counter,
+        ];
+        insert_event(EVENT_SET_UPDATE, &mut data);
+    }
+
+    fn emit_pause_event(market_id: u64, pause: Pause, counter: u64) {
+        let mut data = vec![
+            market_id,
+            pause.trading as u64,
+            pause.claims as u64,
+            counter,
+        ];
+        insert_event(EVENT_PAUSE_UPDATE, &mut data);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn emit_sell_event(player_id: [u64; 2], market_id: u64, sell_type: u64, shares: u64, payout: u64, txid: u64, counter: u64, fee: u64, fee_tier: u64) {
         let mut data = vec![
             txid,
+            market_id,
             player_id[0],
             player_id[1],
             sell_type + 10, // 11 = SELL_YES, 12 = SELL_NO (distinguish from bet events)
             shares,
             payout,
             counter,
+            fee,
+            fee_tier,
         ];
         insert_event(EVENT_BET_UPDATE, &mut data); // Reuse BET_UPDATE event for now
     }
@@ -285,8 +1094,30 @@ pub fn decode_error(e: u32) -> &'static str {
         ERROR_MARKET_ALREADY_RESOLVED => "MarketAlreadyResolved",
         ERROR_INVALID_MARKET_TIME => "InvalidMarketTime",
         ERROR_INVALID_BET_TYPE => "InvalidBetType",
+        ERROR_INVALID_OUTCOME => "InvalidOutcome",
         ERROR_PLAYER_NOT_EXIST => "PlayerNotExist",
         ERROR_PLAYER_ALREADY_EXISTS => "PlayerAlreadyExists",
+        ERROR_MARKET_NOT_EXIST => "MarketNotExist",
+        ERROR_SLIPPAGE_EXCEEDED => "SlippageExceeded",
+        ERROR_ORDER_NOT_EXIST => "OrderNotExist",
+        ERROR_ORDER_BOOK_FULL => "OrderBookFull",
+        ERROR_INVALID_LIMIT_PRICE => "InvalidLimitPrice",
+        ERROR_MARKET_ALREADY_STARTED => "MarketAlreadyStarted",
+        ERROR_DISPUTE_WINDOW_CLOSED => "DisputeWindowClosed",
+        ERROR_INVALID_COMMITTEE => "InvalidCommittee",
+        ERROR_PROPOSAL_EXISTS => "ProposalExists",
+        ERROR_PROPOSAL_NOT_EXIST => "ProposalNotExist",
+        ERROR_MARKET_NOT_ENDED => "MarketNotEnded",
+        ERROR_MARKET_PAUSED => "MarketPaused",
+        ERROR_TRADING_HALTED => "TradingHalted",
+        ERROR_INVALID_BREAKER_LIMITS => "InvalidBreakerLimits",
+        ERROR_POSITION_LIMIT_EXCEEDED => "PositionLimitExceeded",
+        ERROR_OPEN_INTEREST_CAP_EXCEEDED => "OpenInterestCapExceeded",
+        ERROR_INVALID_TRANSFER => "InvalidTransfer",
+        ERROR_INVALID_FEE_SCHEDULE => "InvalidFeeSchedule",
+        ERROR_INVALID_FEE_SPLIT => "InvalidFeeSplit",
+        ERROR_NO_FEES_TO_WITHDRAW => "NoFeesToWithdraw",
+        ERROR_INVALID_REFERRER => "InvalidReferrer",
         _ => "Unknown",
     }
 } 
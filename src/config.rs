@@ -39,6 +39,16 @@ pub const EVENT_PLAYER_UPDATE: u64 = 3;
 
 // Market constants
 pub const PLATFORM_FEE_RATE: u64 = 100; // 1% platform fee (100/10000)
+pub const MAX_FEE_BPS: u64 = 1000; // 10% ceiling of any market's fee schedule (1000/10000)
+// Volume fee tiers: cumulative traded volume at which a tier starts, and its discount on the fee in basis points
+pub const FEE_TIERS: [(u64, u64); 4] = [
+    (0, 0),
+    (10_000_000, 1000),    // 10% off from 10M traded
+    (100_000_000, 2500),   // 25% off from 100M traded
+    (1_000_000_000, 5000), // 50% off from 1B traded
+];
+pub const LP_FEE_SHARE_BPS: u64 = 5000; // 50% of trading fees go to liquidity providers (5000/10000)
+pub const REFERRAL_SHARE_BPS: u64 = 1000; // 10% of a referred player's trading fees go to the referrer (1000/10000)
 
 // Price precision constants
 pub const BASIS_POINTS_PRECISION: u64 = 10000;     // 10,000 basis points = 100%
@@ -56,6 +66,7 @@ pub const FEE_BASIS_POINTS: u64 = 10000; // Same as BASIS_POINTS_PRECISION for f
 pub struct DefaultMarketConfig {
     pub title: &'static str,
     pub description: &'static str,
+    pub resolution_criteria: &'static str,
     pub start_time: u64,
     pub end_time: u64,
     pub resolution_time: u64,
@@ -67,6 +78,7 @@ lazy_static::lazy_static! {
     pub static ref DEFAULT_MARKET: DefaultMarketConfig = DefaultMarketConfig {
         title: "Bitcoin $100K by 2024",
         description: "Will Bitcoin reach $100,000 USD by December 31, 2024?",
+        resolution_criteria: "Resolves YES if the BTC/USD spot price trades at or above $100,000 on any major exchange before the market ends, otherwise NO.",
         start_time: 0,      // Start immediately (counter = 0)
         end_time: 17280,    // End after 1 day (17280 ticks = 86400 seconds)
         resolution_time: 17280, // Resolution time same as end time
@@ -81,6 +93,22 @@ pub const TICKS_PER_MINUTE: u64 = 12;
 pub const TICKS_PER_HOUR: u64 = 720;
 pub const TICKS_PER_DAY: u64 = 17280;
 
+// Challenge period after a proposed resolution, before claims open
+pub const DISPUTE_PERIOD_TICKS: u64 = TICKS_PER_DAY;
+
+// Optimistic resolution: bond posted by a proposer or disputer, and matched by a challenger of a proposal
+pub const RESOLUTION_BOND: u64 = 100000;
+pub const BOND_TREASURY_SHARE_BPS: u64 = 5000; // 50% of the losing bond goes to the treasury (5000/10000)
+
+// Position limits: shares one player may hold per outcome, and traders' outstanding shares per market
+pub const MAX_POSITION_SHARES: u64 = 500_000_000;
+pub const MAX_OPEN_INTEREST: u64 = 5_000_000_000;
+
+// Circuit breaker defaults: halt trading when any outcome price moves too far within a window
+pub const CIRCUIT_BREAKER_MOVE_BPS: u64 = 1500; // 15 percentage points of price (1500/10000)
+pub const CIRCUIT_BREAKER_WINDOW_TICKS: u64 = 5 * TICKS_PER_MINUTE;
+pub const CIRCUIT_BREAKER_COOLDOWN_TICKS: u64 = 15 * TICKS_PER_MINUTE;
+
 impl DefaultMarketConfig {
     /// Convert seconds to ticks
     pub fn seconds_to_ticks(seconds: u64) -> u64 {
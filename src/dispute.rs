@@ -0,0 +1,68 @@
+use serde::Serialize;
+use zkwasm_rest_abi::{StorageData, MERKLE_MAP};
+use crate::market::Market;
+
+/// Bond posted by a position holder disputing an outcome proposed by the admin
+/// or a resolver committee. It is refunded if the final outcome differs from the
+/// disputed one and goes to the treasury otherwise.
+#[derive(Serialize, Clone, Debug)]
+pub struct DisputeBond {
+    pub market_id: u64,
+    pub disputer: [u64; 2],
+    pub outcome: u64, // settled outcome being disputed
+    pub bond: u64,
+}
+
+impl DisputeBond {
+    pub fn get(market_id: u64) -> Option<Self> {
+        let kvpair = unsafe { &mut MERKLE_MAP };
+        let mut data = kvpair.get(&Market::to_dispute_key(market_id));
+        if data.is_empty() {
+            return None;
+        }
+        let mut dispute = Self::from_data(&mut data.iter_mut());
+        dispute.market_id = market_id;
+        Some(dispute)
+    }
+
+    pub fn store(&self) {
+        let mut data = vec![];
+        self.to_data(&mut data);
+        let kvpair = unsafe { &mut MERKLE_MAP };
+        kvpair.set(&Market::to_dispute_key(self.market_id), data.as_slice());
+    }
+
+    pub fn remove(market_id: u64) {
+        let kvpair = unsafe { &mut MERKLE_MAP };
+        kvpair.set(&Market::to_dispute_key(market_id), &[]);
+    }
+
+    /// Returns the refund to the disputer, if the dispute changed the outcome, and
+    /// the treasury's cut, if it did not.
+    pub fn settle(&self, final_outcome: u64) -> (u64, u64) {
+        if self.outcome == final_outcome {
+            (0, self.bond)
+        } else {
+            (self.bond, 0)
+        }
+    }
+}
+
+impl StorageData for DisputeBond {
+    fn from_data(u64data: &mut std::slice::IterMut<u64>) -> Self {
+        DisputeBond {
+            // Filled in by DisputeBond::get
+            market_id: 0,
+            disputer: [*u64data.next().unwrap(), *u64data.next().unwrap()],
+            outcome: *u64data.next().unwrap(),
+            bond: *u64data.next().unwrap(),
+        }
+    }
+
+    fn to_data(&self, data: &mut Vec<u64>) {
+        data.push(self.disputer[0]);
+        data.push(self.disputer[1]);
+        data.push(self.outcome);
+        data.push(self.bond);
+    }
+}
@@ -11,6 +11,27 @@ pub const ERROR_MARKET_ALREADY_RESOLVED: u32 = 7;
 pub const ERROR_INVALID_OUTCOME: u32 = 1010;
 pub const ERROR_INVALID_BET_TYPE: u32 = 1011;
 pub const ERROR_INVALID_MARKET_TIME: u32 = 1012;
+pub const ERROR_MARKET_NOT_EXIST: u32 = 1013;
+pub const ERROR_SLIPPAGE_EXCEEDED: u32 = 1014;
+pub const ERROR_ORDER_NOT_EXIST: u32 = 1015;
+pub const ERROR_ORDER_BOOK_FULL: u32 = 1016;
+pub const ERROR_INVALID_LIMIT_PRICE: u32 = 1017;
+pub const ERROR_MARKET_ALREADY_STARTED: u32 = 1018;
+pub const ERROR_DISPUTE_WINDOW_CLOSED: u32 = 1019;
+pub const ERROR_INVALID_COMMITTEE: u32 = 1020;
+pub const ERROR_PROPOSAL_EXISTS: u32 = 1021;
+pub const ERROR_PROPOSAL_NOT_EXIST: u32 = 1022;
+pub const ERROR_MARKET_NOT_ENDED: u32 = 1023;
+pub const ERROR_MARKET_PAUSED: u32 = 1024;
+pub const ERROR_TRADING_HALTED: u32 = 1025;
+pub const ERROR_INVALID_BREAKER_LIMITS: u32 = 1026;
+pub const ERROR_POSITION_LIMIT_EXCEEDED: u32 = 1027;
+pub const ERROR_OPEN_INTEREST_CAP_EXCEEDED: u32 = 1028;
+pub const ERROR_INVALID_TRANSFER: u32 = 1029;
+pub const ERROR_INVALID_FEE_SCHEDULE: u32 = 1030;
+pub const ERROR_INVALID_FEE_SPLIT: u32 = 1031;
+pub const ERROR_NO_FEES_TO_WITHDRAW: u32 = 1032;
+pub const ERROR_INVALID_REFERRER: u32 = 1033;
 pub const ERROR_PLAYER_NOT_EXIST: u32 = 6;
 pub const ERROR_PLAYER_ALREADY_EXISTS: u32 = 8;
 
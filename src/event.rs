@@ -23,33 +23,42 @@ pub fn insert_event(typ: u64, data: &mut Vec<u64>) {
 // Event type constants for prediction market
 pub const EVENT_PLAYER_UPDATE: u64 = 1;
 pub const EVENT_MARKET_UPDATE: u64 = 2;
-pub const EVENT_BET_UPDATE: u64 = 3; 
+pub const EVENT_BET_UPDATE: u64 = 3;
+pub const EVENT_MARKET_CREATED: u64 = 4;
+pub const EVENT_ORDER_UPDATE: u64 = 5;
+pub const EVENT_LIQUIDITY_UPDATE: u64 = 6;
+pub const EVENT_RESOLUTION_VOTE: u64 = 7;
+pub const EVENT_PROPOSAL_UPDATE: u64 = 8;
+pub const EVENT_MARKET_STATUS: u64 = 9;
+pub const EVENT_PAUSE_UPDATE: u64 = 10;
+pub const EVENT_CIRCUIT_BREAKER: u64 = 11;
+pub const EVENT_SET_UPDATE: u64 = 12;
+pub const EVENT_SHARE_TRANSFER: u64 = 13;
+pub const EVENT_BALANCE_TRANSFER: u64 = 14;
 
 
 pub struct MarketEvent {
-    // Virtual liquidity for AMM pricing
-    yes_liquidity: u64,
-    no_liquidity: u64,
+    // Virtual liquidity per outcome for AMM pricing
+    liquidity: Vec<u64>,
 }
 
 impl StorageData for MarketEvent {
     fn from_data(u64data: &mut std::slice::IterMut<u64>) -> Self {
+        let outcome_count = *u64data.next().unwrap();
         MarketEvent {
-            yes_liquidity: *u64data.next().unwrap(),
-            no_liquidity: *u64data.next().unwrap(),
+            liquidity: (0..outcome_count).map(|_| *u64data.next().unwrap()).collect(),
         }
     }
     fn to_data(&self, data: &mut Vec<u64>) {
-        data.push(self.yes_liquidity);
-        data.push(self.no_liquidity);
+        data.push(self.liquidity.len() as u64);
+        data.extend_from_slice(&self.liquidity);
     }
 }
 
 impl From<&MarketData> for MarketEvent {
     fn from(m: &MarketData) -> MarketEvent {
         MarketEvent {
-            yes_liquidity: m.yes_liquidity,
-            no_liquidity: m.no_liquidity,
+            liquidity: m.liquidity.clone(),
         }
     }
 }
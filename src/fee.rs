@@ -0,0 +1,143 @@
+use serde::Serialize;
+use zkwasm_rest_abi::StorageData;
+use crate::config::{FEE_BASIS_POINTS, FEE_TIERS, LP_FEE_SHARE_BPS, MAX_FEE_BPS, PLATFORM_FEE_RATE, PRICE_PRECISION, REFERRAL_SHARE_BPS};
+use crate::error::*;
+
+/// Per-market fee curve, in basis points of the trade amount.
+/// The rate moves linearly from `base_bps` at a 50% price to `edge_bps` at 0% or 100%,
+/// and `ramp_bps` is added linearly over the last `ramp_ticks` before the market closes.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct FeeSchedule {
+    pub base_bps: u64,
+    pub edge_bps: u64,
+    pub ramp_ticks: u64, // 0 disables the pre-close ramp
+    pub ramp_bps: u64,
+}
+
+impl Default for FeeSchedule {
+    // The flat platform fee
+    fn default() -> Self {
+        FeeSchedule {
+            base_bps: PLATFORM_FEE_RATE,
+            edge_bps: PLATFORM_FEE_RATE,
+            ramp_ticks: 0,
+            ramp_bps: 0,
+        }
+    }
+}
+
+impl FeeSchedule {
+    pub fn new(base_bps: u64, edge_bps: u64, ramp_ticks: u64, ramp_bps: u64) -> Result<Self, u32> {
+        // The highest rate the curve can reach must stay within MAX_FEE_BPS
+        let peak = base_bps.max(edge_bps).checked_add(ramp_bps).ok_or(ERROR_INVALID_FEE_SCHEDULE)?;
+        if peak > MAX_FEE_BPS || (ramp_ticks == 0 && ramp_bps != 0) {
+            return Err(ERROR_INVALID_FEE_SCHEDULE);
+        }
+        Ok(FeeSchedule { base_bps, edge_bps, ramp_ticks, ramp_bps })
+    }
+
+    /// Rate for a trade at spot `price` (PRICE_PRECISION = 1.0), `time_to_close` ticks before end_time.
+    pub fn rate_bps(&self, price: u64, time_to_close: u64) -> u64 {
+        let half = PRICE_PRECISION / 2;
+        let distance = price.abs_diff(half).min(half) as u128;
+        let price_rate = if self.edge_bps >= self.base_bps {
+            self.base_bps + ((self.edge_bps - self.base_bps) as u128 * distance / half as u128) as u64
+        } else {
+            self.base_bps - ((self.base_bps - self.edge_bps) as u128 * distance / half as u128) as u64
+        };
+
+        let ramp_rate = if time_to_close < self.ramp_ticks {
+            let elapsed = (self.ramp_ticks - time_to_close) as u128;
+            (self.ramp_bps as u128 * elapsed / self.ramp_ticks as u128) as u64
+        } else {
+            0
+        };
+        price_rate + ramp_rate
+    }
+}
+
+/// Per-market weights, in basis points adding up to FEE_BASIS_POINTS, of how each trading fee is shared.
+/// The LP share goes to the platform while the market has no LP shares, as does the referrer share
+/// of a trader without a referrer.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct FeeSplit {
+    pub platform_bps: u64,
+    pub creator_bps: u64,
+    pub lp_bps: u64,
+    pub referrer_bps: u64,
+}
+
+impl Default for FeeSplit {
+    // Half to the LPs and the referral rebate, the rest to the platform
+    fn default() -> Self {
+        FeeSplit {
+            platform_bps: FEE_BASIS_POINTS - LP_FEE_SHARE_BPS - REFERRAL_SHARE_BPS,
+            creator_bps: 0,
+            lp_bps: LP_FEE_SHARE_BPS,
+            referrer_bps: REFERRAL_SHARE_BPS,
+        }
+    }
+}
+
+impl FeeSplit {
+    pub fn new(platform_bps: u64, creator_bps: u64, lp_bps: u64, referrer_bps: u64) -> Result<Self, u32> {
+        let total = [platform_bps, creator_bps, lp_bps, referrer_bps].iter()
+            .try_fold(0u64, |total, &bps| total.checked_add(bps));
+        if total != Some(FEE_BASIS_POINTS) {
+            return Err(ERROR_INVALID_FEE_SPLIT);
+        }
+        Ok(FeeSplit { platform_bps, creator_bps, lp_bps, referrer_bps })
+    }
+
+    /// Part of `fee` owed to a beneficiary with weight `bps`, rounded down; the platform keeps the dust.
+    pub fn share(fee: u64, bps: u64) -> u64 {
+        (fee as u128 * bps as u128 / FEE_BASIS_POINTS as u128) as u64
+    }
+}
+
+/// Index into FEE_TIERS of the highest tier a player's cumulative traded volume has reached.
+pub fn fee_tier(traded_volume: u64) -> u64 {
+    FEE_TIERS.iter().rposition(|&(min_volume, _)| traded_volume >= min_volume).unwrap_or(0) as u64
+}
+
+/// Rate after a tier's discount, rounded down in the player's favour.
+pub fn discounted_rate_bps(rate_bps: u64, fee_tier: u64) -> u64 {
+    let (_, discount_bps) = FEE_TIERS[fee_tier as usize];
+    rate_bps * (FEE_BASIS_POINTS - discount_bps) / FEE_BASIS_POINTS
+}
+
+impl StorageData for FeeSplit {
+    fn from_data(u64data: &mut std::slice::IterMut<u64>) -> Self {
+        FeeSplit {
+            platform_bps: *u64data.next().unwrap(),
+            creator_bps: *u64data.next().unwrap(),
+            lp_bps: *u64data.next().unwrap(),
+            referrer_bps: *u64data.next().unwrap(),
+        }
+    }
+
+    fn to_data(&self, data: &mut Vec<u64>) {
+        data.push(self.platform_bps);
+        data.push(self.creator_bps);
+        data.push(self.lp_bps);
+        data.push(self.referrer_bps);
+    }
+}
+
+impl StorageData for FeeSchedule {
+    fn from_data(u64data: &mut std::slice::IterMut<u64>) -> Self {
+        FeeSchedule {
+            base_bps: *u64data.next().unwrap(),
+            edge_bps: *u64data.next().unwrap(),
+            ramp_ticks: *u64data.next().unwrap(),
+            ramp_bps: *u64data.next().unwrap(),
+        }
+    }
+
+    fn to_data(&self, data: &mut Vec<u64>) {
+        data.push(self.base_bps);
+        data.push(self.edge_bps);
+        data.push(self.ramp_ticks);
+        data.push(self.ramp_bps);
+    }
+}
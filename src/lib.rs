@@ -10,6 +10,13 @@ pub mod player;
 pub mod settlement;
 pub mod state;
 pub mod market;
+pub mod pricing;
+pub mod order;
+pub mod dispute;
+pub mod resolver;
+pub mod proposal;
+pub mod circuit_breaker;
+pub mod fee;
 pub mod math_safe;
 pub mod security_tests;
 
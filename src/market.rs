@@ -1,158 +1,543 @@
 use serde::Serialize;
-use zkwasm_rest_abi::StorageData;
-use crate::config::{DEFAULT_MARKET, PRICE_PRECISION};
+use zkwasm_rest_abi::{StorageData, MERKLE_MAP};
+use crate::circuit_breaker::CircuitBreaker;
+use crate::fee::{discounted_rate_bps, FeeSchedule, FeeSplit};
+use crate::config::{DEFAULT_MARKET, DISPUTE_PERIOD_TICKS, MAX_OPEN_INTEREST, PRICE_PRECISION};
 use crate::error::*;
 use crate::math_safe::*;
+use crate::pricing::PricingModel;
 
-#[derive(Serialize, Clone, Debug)]
-pub struct MarketData {
+// 二元市场的结果索引，与 bet_type / sell_type 一致
+pub const OUTCOME_NO: u64 = 0;
+pub const OUTCOME_YES: u64 = 1;
+// 标量市场的结果索引：SHORT 在下界全额结算，LONG 在上界全额结算
+pub const OUTCOME_SHORT: u64 = 0;
+pub const OUTCOME_LONG: u64 = 1;
+// Resolving to this outcome voids the market and refunds traders
+pub const OUTCOME_INVALID: u64 = u64::MAX;
+
+// 暂停命令作用于全部市场时使用的 market_id
+pub const ALL_MARKETS: u64 = u64::MAX;
+
+// 市场最长文本字节数，防止交易参数过大
+pub const MAX_MARKET_TEXT_BYTES: usize = 1024;
+
+// Human readable market text, stored under its own merkle key since it never changes after creation
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct MarketText {
     pub title: String,
     pub description: String,
+    pub resolution_criteria: String,
+    pub outcomes: Vec<String>, // Outcome labels, indexed like the market's liquidity
+}
+
+impl MarketText {
+    // Decode text packed into transaction params, rejecting malformed or oversized input
+    pub fn decode(words: &[u64]) -> Option<Self> {
+        let mut words = words.to_vec();
+        let mut u64data = words.iter_mut();
+        let title = unpack_string(&mut u64data)?;
+        let description = unpack_string(&mut u64data)?;
+        let resolution_criteria = unpack_string(&mut u64data)?;
+        let outcome_count = *u64data.next()? as usize;
+        if outcome_count > MAX_OUTCOMES {
+            return None;
+        }
+        let mut outcomes = Vec::with_capacity(outcome_count);
+        for _ in 0..outcome_count {
+            outcomes.push(unpack_string(&mut u64data)?);
+        }
+        let text = MarketText {
+            title,
+            description,
+            resolution_criteria,
+            outcomes,
+        };
+        if u64data.next().is_some() {
+            return None;
+        }
+        Some(text)
+    }
+}
+
+// Strings are packed as their byte length followed by little-endian 8-byte words
+fn pack_string(s: &str, data: &mut Vec<u64>) {
+    let bytes = s.as_bytes();
+    data.push(bytes.len() as u64);
+    for chunk in bytes.chunks(8) {
+        let mut word = [0u8; 8];
+        word[..chunk.len()].copy_from_slice(chunk);
+        data.push(u64::from_le_bytes(word));
+    }
+}
+
+fn unpack_string(u64data: &mut std::slice::IterMut<u64>) -> Option<String> {
+    let len = *u64data.next()? as usize;
+    if len > MAX_MARKET_TEXT_BYTES {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(len.div_ceil(8) * 8);
+    for _ in 0..len.div_ceil(8) {
+        bytes.extend_from_slice(&u64data.next()?.to_le_bytes());
+    }
+    bytes.truncate(len);
+    String::from_utf8(bytes).ok()
+}
+
+impl StorageData for MarketText {
+    fn from_data(u64data: &mut std::slice::IterMut<u64>) -> Self {
+        let title = unpack_string(u64data).unwrap();
+        let description = unpack_string(u64data).unwrap();
+        let resolution_criteria = unpack_string(u64data).unwrap();
+        let outcome_count = *u64data.next().unwrap();
+        MarketText {
+            title,
+            description,
+            resolution_criteria,
+            outcomes: (0..outcome_count).map(|_| unpack_string(u64data).unwrap()).collect(),
+        }
+    }
+
+    fn to_data(&self, data: &mut Vec<u64>) {
+        pack_string(&self.title, data);
+        pack_string(&self.description, data);
+        pack_string(&self.resolution_criteria, data);
+        data.push(self.outcomes.len() as u64);
+        for outcome in self.outcomes.iter() {
+            pack_string(outcome, data);
+        }
+    }
+}
+
+// 市场生命周期状态，存储编码为声明顺序 0..=6
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MarketStatus {
+    Scheduled, // 尚未开始，可以取消
+    Open,      // 交易中
+    Closed,    // 交易结束，等待解决
+    Proposed,  // 已提出结果，处于争议期
+    Disputed,  // 结果被质疑，等待裁定
+    Finalized, // 结果生效，可以领取
+    Cancelled, // 开始前取消，按无效退款
+}
+
+impl MarketStatus {
+    pub fn code(&self) -> u64 {
+        *self as u64
+    }
+
+    pub fn from_code(code: u64) -> Self {
+        match code {
+            0 => MarketStatus::Scheduled,
+            1 => MarketStatus::Open,
+            2 => MarketStatus::Closed,
+            3 => MarketStatus::Proposed,
+            4 => MarketStatus::Disputed,
+            5 => MarketStatus::Finalized,
+            _ => MarketStatus::Cancelled,
+        }
+    }
+}
+
+// 管理员暂停开关：trading 停止交易、注入流动性和挂单成交，claims 另外停止领取
+#[derive(Serialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Pause {
+    pub trading: bool,
+    pub claims: bool,
+}
+
+impl Pause {
+    // 存储编码：bit0 = trading，bit1 = claims
+    pub fn code(&self) -> u64 {
+        (self.trading as u64) | ((self.claims as u64) << 1)
+    }
+
+    pub fn from_code(code: u64) -> Self {
+        Pause {
+            trading: code & 1 != 0,
+            claims: code & 2 != 0,
+        }
+    }
+}
+
+// 标量市场的数值区间，解决值在区间外时截断到边界
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct ScalarRange {
+    pub lower: u64,
+    pub upper: u64,
+}
+
+impl ScalarRange {
+    // LONG 一方分得的奖池比例（PRICE_PRECISION = 1.0），随解决值线性变化
+    pub fn long_ratio(&self, value: u64) -> Result<u64, u32> {
+        let value = value.clamp(self.lower, self.upper);
+        safe_div_high_precision(value - self.lower, PRICE_PRECISION, self.upper - self.lower)
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct MarketData {
+    #[serde(flatten)]
+    pub text: MarketText,
     pub start_time: u64,
     pub end_time: u64,
     pub resolution_time: u64,
-    // AMM virtual liquidity (for pricing only)
-    pub yes_liquidity: u64,
-    pub no_liquidity: u64,
+    // AMM pricing state per outcome (for pricing only):
+    // virtual liquidity for CPMM, shares sold by the market maker for LMSR
+    pub liquidity: Vec<u64>,
+    pub pricing: PricingModel,
     // Actual prize pool from user bets
     pub prize_pool: u64,
     pub total_volume: u64,
-    pub total_yes_shares: u64,  // Total YES shares issued
-    pub total_no_shares: u64,   // Total NO shares issued
-    pub resolved: bool,
-    pub outcome: Option<bool>, // None = unresolved, Some(true) = Yes wins, Some(false) = No wins
-    pub total_fees_collected: u64,
+    pub total_shares: Vec<u64>, // Total shares issued per outcome
+    // Stored status; time-driven transitions are applied by status_at / advance
+    pub status: MarketStatus,
+    // None = unresolved, Some(i) = outcome i wins; for scalar markets Some(v) = settlement value
+    pub outcome: Option<u64>,
+    pub invalid: bool, // Resolved as invalid or cancelled, claims refund cost basis
+    // The resolved outcome is only a proposal until this tick; claims wait for it
+    pub dispute_end: u64,
+    pub total_fees_collected: u64, // Platform share of fees, withdrawn by the admin
+    // Complete sets held for liquidity providers, one per unit deposited: the collateral sits in the
+    // prize pool, their shares in total_shares and their cost in total_cost_basis, and it scales the AMM depth
+    pub lp_pool: u64,
+    pub lp_fees: u64, // LP share of trading fees, paid out when LP shares are burned
+    pub total_lp_shares: u64,
+    // Sum of the traders' net cost basis and the LP pool, the denominator of invalid refunds
+    pub total_cost_basis: u64,
+    pub scalar: Option<ScalarRange>, // Range of a scalar market, outcomes are [SHORT, LONG]
+    pub pause: Pause, // Set by the admin, on top of the global pause
+    pub breaker: CircuitBreaker,
+    pub fee_schedule: FeeSchedule, // Fee curve set by the admin, flat PLATFORM_FEE_RATE by default
+    pub fee_split: FeeSplit,
+    pub creator: [u64; 2], // Player credited with the creator share of fees
+    pub creator_fees: u64, // Creator share of trading fees, withdrawn by the creator
+    // Referrer share of trading fees, moved to the trader's referrer, or to the platform without one
+    pub referrer_fees: u64,
 }
 
 impl MarketData {
     pub fn new(title: String, description: String, start_time: u64, end_time: u64, resolution_time: u64) -> Result<Self, u32> {
-        // 验证初始流动性
-        validate_liquidity(DEFAULT_MARKET.initial_yes_liquidity)?;
-        validate_liquidity(DEFAULT_MARKET.initial_no_liquidity)?;
-        
+        Self::with_liquidity(
+            MarketText {
+                title,
+                description,
+                resolution_criteria: String::new(),
+                outcomes: vec!["No".to_string(), "Yes".to_string()],
+            },
+            start_time,
+            end_time,
+            resolution_time,
+            vec![DEFAULT_MARKET.initial_no_liquidity, DEFAULT_MARKET.initial_yes_liquidity],
+        )
+    }
+
+    // liquidity[i] 为结果 i 的初始流动性，二元市场为 [NO, YES]
+    pub fn with_liquidity(
+        text: MarketText,
+        start_time: u64,
+        end_time: u64,
+        resolution_time: u64,
+        liquidity: Vec<u64>,
+    ) -> Result<Self, u32> {
+        Self::with_pricing(text, start_time, end_time, resolution_time, PricingModel::Cpmm, liquidity)
+    }
+
+    // state[i] 为结果 i 的初始定价状态：CPMM 为流动性，LMSR 为初始份额 q_i（全 0 即均等价格）
+    pub fn with_pricing(
+        text: MarketText,
+        start_time: u64,
+        end_time: u64,
+        resolution_time: u64,
+        pricing: PricingModel,
+        state: Vec<u64>,
+    ) -> Result<Self, u32> {
+        // 验证时间顺序：开始 < 结束 <= 解决
+        if start_time >= end_time || end_time > resolution_time {
+            return Err(ERROR_INVALID_MARKET_TIME);
+        }
+
+        // 验证结果数量与结果名称
+        if state.len() < MIN_OUTCOMES || state.len() > MAX_OUTCOMES || text.outcomes.len() != state.len() {
+            return Err(ERROR_INVALID_OUTCOME);
+        }
+
+        // 验证初始定价状态
+        pricing.engine().validate_state(&state)?;
+
+        let outcome_count = state.len();
         Ok(MarketData {
-            title,
-            description,
+            text,
             start_time,
             end_time,
             resolution_time,
-            // Virtual liquidity for AMM pricing
-            yes_liquidity: DEFAULT_MARKET.initial_yes_liquidity,
-            no_liquidity: DEFAULT_MARKET.initial_no_liquidity,
+            // Virtual state for AMM pricing
+            liquidity: state,
+            pricing,
             // Real money tracking
             prize_pool: 0,
             total_volume: 0,
-            total_yes_shares: 0,
-            total_no_shares: 0,
-            resolved: false,
+            total_shares: vec![0; outcome_count],
+            status: MarketStatus::Scheduled,
             outcome: None,
+            invalid: false,
+            dispute_end: 0,
             total_fees_collected: 0,
+            lp_pool: 0,
+            lp_fees: 0,
+            total_lp_shares: 0,
+            total_cost_basis: 0,
+            scalar: None,
+            pause: Pause::default(),
+            breaker: CircuitBreaker::default(),
+            fee_schedule: FeeSchedule::default(),
+            fee_split: FeeSplit::default(),
+            creator: [0, 0],
+            creator_fees: 0,
+            referrer_fees: 0,
         })
     }
 
+    // 标量市场：两个结果 [SHORT, LONG]，按解决值在区间内的位置线性分配奖池
+    pub fn with_scalar_range(
+        text: MarketText,
+        start_time: u64,
+        end_time: u64,
+        resolution_time: u64,
+        pricing: PricingModel,
+        state: Vec<u64>,
+        range: ScalarRange,
+    ) -> Result<Self, u32> {
+        // 解决值加 1 后存储，上界需与 OUTCOME_INVALID 区分
+        if state.len() != 2 || range.lower >= range.upper || range.upper >= OUTCOME_INVALID - 1 {
+            return Err(ERROR_INVALID_OUTCOME);
+        }
+        let mut market = Self::with_pricing(text, start_time, end_time, resolution_time, pricing, state)?;
+        market.scalar = Some(range);
+        Ok(market)
+    }
+
+    // status 之后按时间到期的下一个状态：开始、结束与争议期结束，每次只推进一步
+    fn next_status(&self, status: MarketStatus, current_time: u64) -> Option<MarketStatus> {
+        match status {
+            MarketStatus::Scheduled if current_time >= self.start_time => Some(MarketStatus::Open),
+            MarketStatus::Open if current_time >= self.end_time => Some(MarketStatus::Closed),
+            MarketStatus::Proposed if current_time >= self.dispute_end => Some(MarketStatus::Finalized),
+            _ => None,
+        }
+    }
+
+    // 当前时间下的状态：依次应用所有到期的转换
+    pub fn status_at(&self, current_time: u64) -> MarketStatus {
+        let mut status = self.status;
+        while let Some(next) = self.next_status(status, current_time) {
+            status = next;
+        }
+        status
+    }
+
+    // 写入下一个到期的状态，返回是否推进；每个 tick 逐步调用，每一步发出一个状态事件
+    pub fn step(&mut self, current_time: u64) -> bool {
+        match self.next_status(self.status, current_time) {
+            Some(status) => {
+                self.status = status;
+                true
+            },
+            None => false,
+        }
+    }
+
+    // 写入按时间推进后的状态，返回状态是否改变
+    pub fn advance(&mut self, current_time: u64) -> bool {
+        let mut changed = false;
+        while self.step(current_time) {
+            changed = true;
+        }
+        changed
+    }
+
+    // 已最终生效或取消，状态不再随时间变化
+    pub fn is_settled(&self) -> bool {
+        matches!(self.status, MarketStatus::Finalized | MarketStatus::Cancelled)
+    }
+
     pub fn is_active(&self, current_time: u64) -> bool {
-        current_time >= self.start_time && current_time < self.end_time && !self.resolved
+        self.status_at(current_time) == MarketStatus::Open
+    }
+
+    // 已提出、裁定或取消解决结果，交易与流动性注入停止
+    pub fn is_resolved(&self) -> bool {
+        matches!(
+            self.status,
+            MarketStatus::Proposed | MarketStatus::Disputed | MarketStatus::Finalized | MarketStatus::Cancelled
+        )
     }
 
     pub fn can_resolve(&self, current_time: u64) -> bool {
-        current_time >= self.resolution_time && !self.resolved
+        match self.status_at(current_time) {
+            MarketStatus::Closed => current_time >= self.resolution_time,
+            MarketStatus::Proposed | MarketStatus::Disputed => true,
+            _ => false,
+        }
+    }
+
+    pub fn outcome_count(&self) -> u64 {
+        self.liquidity.len() as u64
     }
 
-    // 安全的 YES 价格计算
+    // 验证结果索引（bet_type / sell_type）
+    pub fn validate_outcome(&self, outcome: u64) -> Result<usize, u32> {
+        if outcome >= self.outcome_count() {
+            return Err(ERROR_INVALID_BET_TYPE);
+        }
+        Ok(outcome as usize)
+    }
+
+    // 安全的结果价格计算，所有结果价格之和为 PRICE_PRECISION
+    pub fn get_price(&self, outcome: u64) -> Result<u64, u32> {
+        let index = self.validate_outcome(outcome)?;
+        self.pricing.engine().price(&self.liquidity, index)
+    }
+
+    // 安全的 YES 价格计算（二元市场）
     pub fn get_yes_price(&self) -> Result<u64, u32> {
-        let total_liquidity = safe_add(self.yes_liquidity, self.no_liquidity)?;
-        if total_liquidity == 0 {
-            return Ok(PRICE_PRECISION / 2); // 50% if no liquidity
+        self.get_price(OUTCOME_YES)
+    }
+
+    // 铸造完整份额组：每个结果各 amount 份，抵押品全部进入奖池，不改变 AMM 价格，不收费
+    pub fn mint_set(&mut self, amount: u64) -> Result<(), u32> {
+        validate_bet_amount(amount)?;
+        for shares in self.total_shares.iter_mut() {
+            *shares = safe_add(*shares, amount)?;
         }
-        calculate_price_safe(self.no_liquidity, total_liquidity)
+        self.prize_pool = safe_add(self.prize_pool, amount)?;
+        Ok(())
     }
 
-    // 安全的 NO 价格计算
-    pub fn get_no_price(&self) -> Result<u64, u32> {
-        let total_liquidity = safe_add(self.yes_liquidity, self.no_liquidity)?;
-        if total_liquidity == 0 {
-            return Ok(PRICE_PRECISION / 2); // 50% if no liquidity
+    // 赎回完整份额组：每个结果各销毁 amount 份，从奖池取回等额抵押品
+    pub fn redeem_set(&mut self, amount: u64) -> Result<(), u32> {
+        validate_bet_amount(amount)?;
+        if self.total_shares.iter().any(|shares| *shares < amount) {
+            return Err(ERROR_INSUFFICIENT_BALANCE);
+        }
+        self.prize_pool = safe_sub(self.prize_pool, amount)?;
+        for shares in self.total_shares.iter_mut() {
+            *shares -= amount;
         }
-        calculate_price_safe(self.yes_liquidity, total_liquidity)
+        Ok(())
+    }
+
+    // 未平仓份额：交易者持有的所有结果份额之和，不含 LP 池持有的份额组
+    pub fn open_interest(&self) -> Result<u64, u32> {
+        self.total_shares.iter()
+            .try_fold(0, |total, shares| safe_add(total, safe_sub(*shares, self.lp_pool)?))
     }
 
-    // 统一的份额计算函数（bet_type: 1=YES, 0=NO）
-    pub fn calculate_shares(&self, bet_type: u64, bet_amount: u64) -> Result<u64, u32> {
+    // 交易后检查市场未平仓份额上限
+    pub fn check_open_interest(&self) -> Result<(), u32> {
+        if self.open_interest()? > MAX_OPEN_INTEREST {
+            return Err(ERROR_OPEN_INTEREST_CAP_EXCEEDED);
+        }
+        Ok(())
+    }
+
+    // 所有结果的当前价格
+    pub fn prices(&self) -> Result<Vec<u64>, u32> {
+        (0..self.outcome_count()).map(|outcome| self.get_price(outcome)).collect()
+    }
+
+    // 每个 tick 记录熔断窗口的参考价格（所有结果），返回是否开始了新窗口
+    pub fn roll_price_window(&mut self, current_time: u64) -> Result<bool, u32> {
+        let prices = self.prices()?;
+        Ok(self.breaker.roll(prices, current_time))
+    }
+
+    // 交易后检查每个结果的价格变动，任一超过阈值则熔断，返回是否触发
+    pub fn check_circuit_breaker(&mut self, current_time: u64) -> Result<bool, u32> {
+        let prices = self.prices()?;
+        self.breaker.trip(&prices, current_time)
+    }
+
+    // 安全的 NO 价格计算（二元市场）
+    pub fn get_no_price(&self) -> Result<u64, u32> {
+        self.get_price(OUTCOME_NO)
+    }
+
+    // 当前费率（基点）：按交易结果的现价和距离结束的时间取费率曲线上的值
+    pub fn fee_rate_bps(&self, outcome: u64, current_time: u64) -> Result<u64, u32> {
+        let price = self.get_price(outcome)?;
+        Ok(self.fee_schedule.rate_bps(price, self.end_time.saturating_sub(current_time)))
+    }
+
+    // 玩家实际支付的费率：在当前费率上扣除交易量等级的折扣
+    pub fn trade_fee_bps(&self, outcome: u64, current_time: u64, fee_tier: u64) -> Result<u64, u32> {
+        Ok(discounted_rate_bps(self.fee_rate_bps(outcome, current_time)?, fee_tier))
+    }
+
+    // 统一的份额计算函数（bet_type 为结果索引，二元市场 1=YES, 0=NO）
+    pub fn calculate_shares(&self, bet_type: u64, bet_amount: u64, fee_bps: u64) -> Result<u64, u32> {
         validate_bet_amount(bet_amount)?;
+        let index = self.validate_outcome(bet_type)?;
         
-        let net_amount = calculate_net_amount_safe(bet_amount)?;
+        let net_amount = calculate_net_amount_at_rate(bet_amount, fee_bps)?;
         
         // 安全的 AMM 计算
-        let k = calculate_k_safe(self.yes_liquidity, self.no_liquidity)?;
-        let is_yes_bet = bet_type == 1;
-        
-        let (new_yes_liquidity, new_no_liquidity, original_liquidity) = if is_yes_bet {
-            let new_no = safe_add(self.no_liquidity, net_amount)?;
-            let new_yes = calculate_new_liquidity_safe(k, new_no)?;
-            (new_yes, new_no, self.yes_liquidity)
-        } else {
-            let new_yes = safe_add(self.yes_liquidity, net_amount)?;
-            let new_no = calculate_new_liquidity_safe(k, new_yes)?;
-            (new_yes, new_no, self.no_liquidity)
-        };
+        let (_, shares) = self.pricing.engine().buy(&self.liquidity, index, net_amount)?;
         
-        if original_liquidity >= if is_yes_bet { new_yes_liquidity } else { new_no_liquidity } {
-            let shares = safe_sub(original_liquidity, if is_yes_bet { new_yes_liquidity } else { new_no_liquidity })?;
+        if shares > 0 {
             validate_shares(shares)?;
-            Ok(shares)
-        } else {
-            Ok(0)
         }
+        Ok(shares)
     }
 
 
 
     // 统一的卖出份额计算（返回净收益和费用）
-    pub fn calculate_sell_details(&self, sell_type: u64, shares_to_sell: u64) -> Result<(u64, u64), u32> {
+    pub fn calculate_sell_details(&self, sell_type: u64, shares_to_sell: u64, fee_bps: u64) -> Result<(u64, u64), u32> {
         validate_shares(shares_to_sell)?;
+        let index = self.validate_outcome(sell_type)?;
         
-        let (total_shares, is_yes_sell) = if sell_type == 1 {
-            (self.total_yes_shares, true)
-        } else {
-            (self.total_no_shares, false)
-        };
-        
-        if total_shares == 0 {
+        if self.total_shares[index] == 0 {
             return Ok((0, 0));
         }
         
         // 安全的 AMM 计算
-        let k = calculate_k_safe(self.yes_liquidity, self.no_liquidity)?;
-        let (new_yes_liquidity, new_no_liquidity) = if is_yes_sell {
-            let new_yes = safe_add(self.yes_liquidity, shares_to_sell)?;
-            let new_no = calculate_new_liquidity_safe(k, new_yes)?;
-            (new_yes, new_no)
-        } else {
-            let new_no = safe_add(self.no_liquidity, shares_to_sell)?;
-            let new_yes = calculate_new_liquidity_safe(k, new_no)?;
-            (new_yes, new_no)
-        };
-        
-        let gross_amount = if is_yes_sell {
-            if self.no_liquidity >= new_no_liquidity {
-                safe_sub(self.no_liquidity, new_no_liquidity)?
-            } else {
-                return Ok((0, 0));
-            }
-        } else {
-            if self.yes_liquidity >= new_yes_liquidity {
-                safe_sub(self.yes_liquidity, new_yes_liquidity)?
-            } else {
-                return Ok((0, 0));
-            }
-        };
+        let (_, gross_amount) = self.pricing.engine().sell(&self.liquidity, index, shares_to_sell)?;
         
-        let fee = calculate_fee_safe(gross_amount)?;
+        let fee = calculate_fee_at_rate(gross_amount, fee_bps)?;
         let net_payout = safe_sub(gross_amount, fee)?;
         
         Ok((net_payout, fee))
     }
 
+    // 按玩家的滑点限制报价：投注份额不得少于 min_shares_out
+    pub fn quote_bet(&self, bet_type: u64, bet_amount: u64, fee_bps: u64, min_shares_out: u64) -> Result<u64, u32> {
+        let shares = self.calculate_shares(bet_type, bet_amount, fee_bps)?;
+        if shares < min_shares_out {
+            return Err(ERROR_SLIPPAGE_EXCEEDED);
+        }
+        Ok(shares)
+    }
+
+    // 买入精确份额的含费成本不得超过 max_cost，返回成本和费用
+    pub fn quote_cost(&self, bet_type: u64, shares: u64, fee_bps: u64, max_cost: u64) -> Result<(u64, u64), u32> {
+        let (cost, fee) = self.calculate_cost(bet_type, shares, fee_bps)?;
+        if cost > max_cost {
+            return Err(ERROR_SLIPPAGE_EXCEEDED);
+        }
+        Ok((cost, fee))
+    }
+
+    // 卖出的净收益不得少于 min_payout，返回净收益和费用
+    pub fn quote_sale(&self, sell_type: u64, shares_to_sell: u64, fee_bps: u64, min_payout: u64) -> Result<(u64, u64), u32> {
+        let (payout, fee) = self.calculate_sell_details(sell_type, shares_to_sell, fee_bps)?;
+        if payout < min_payout {
+            return Err(ERROR_SLIPPAGE_EXCEEDED);
+        }
+        Ok((payout, fee))
+    }
+
 
 
     // // 统一的买入价格计算（bet_type: 1=YES, 0=NO）- 前端分析用，后端不使用
@@ -209,57 +594,79 @@ impl MarketData {
     //     }
     // }
 
-    // 统一的投注函数（bet_type: 1=YES, 0=NO）
-    pub fn place_bet(&mut self, bet_type: u64, bet_amount: u64) -> Result<u64, u32> {
+    // 统一的投注函数（bet_type 为结果索引，二元市场 1=YES, 0=NO）
+    pub fn place_bet(&mut self, bet_type: u64, bet_amount: u64, fee_bps: u64) -> Result<u64, u32> {
         validate_bet_amount(bet_amount)?;
 
-        let shares = self.calculate_shares(bet_type, bet_amount)?;
+        let shares = self.calculate_shares(bet_type, bet_amount, fee_bps)?;
         if shares == 0 {
             return Err(ERROR_INVALID_BET_AMOUNT);
         }
 
-        let fee = calculate_fee_safe(bet_amount)?;
+        let fee = calculate_fee_at_rate(bet_amount, fee_bps)?;
         let net_amount = safe_sub(bet_amount, fee)?;
-        let is_yes_bet = bet_type == 1;
+        let index = bet_type as usize;
         
-        // 安全更新 AMM 流动性
-        let k = calculate_k_safe(self.yes_liquidity, self.no_liquidity)?;
-        if is_yes_bet {
-            self.no_liquidity = safe_add(self.no_liquidity, net_amount)?;
-            self.yes_liquidity = calculate_new_liquidity_safe(k, self.no_liquidity)?;
-            self.total_yes_shares = safe_add(self.total_yes_shares, shares)?;
-        } else {
-            self.yes_liquidity = safe_add(self.yes_liquidity, net_amount)?;
-            self.no_liquidity = calculate_new_liquidity_safe(k, self.yes_liquidity)?;
-            self.total_no_shares = safe_add(self.total_no_shares, shares)?;
-        }
+        // 安全更新 AMM 定价状态
+        let (new_liquidity, _) = self.pricing.engine().buy(&self.liquidity, index, net_amount)?;
+        self.liquidity = new_liquidity;
+        self.total_shares[index] = safe_add(self.total_shares[index], shares)?;
         
         // 安全更新状态
         self.prize_pool = safe_add(self.prize_pool, net_amount)?;
         self.total_volume = safe_add(self.total_volume, bet_amount)?;
-        self.total_fees_collected = safe_add(self.total_fees_collected, fee)?;
+        self.collect_fee(fee)?;
         
         Ok(shares)
     }
 
 
 
-    // 统一的卖出函数（sell_type: 1=YES, 0=NO）
-    pub fn sell_shares(&mut self, sell_type: u64, shares_to_sell: u64) -> Result<u64, u32> {
-        validate_shares(shares_to_sell)?;
+    // 买入精确份额的成本（返回含费总额和费用，均向上取整）
+    pub fn calculate_cost(&self, bet_type: u64, shares: u64, fee_bps: u64) -> Result<(u64, u64), u32> {
+        validate_shares(shares)?;
+        let index = self.validate_outcome(bet_type)?;
 
-        let (total_shares, is_yes_sell) = if sell_type == 1 {
-            (self.total_yes_shares, true)
-        } else {
-            (self.total_no_shares, false)
-        };
+        let (_, net_amount) = self.pricing.engine().cost(&self.liquidity, index, shares)?;
+        let gross_amount = calculate_gross_amount_at_rate(net_amount, fee_bps)?;
+        validate_bet_amount(gross_amount)?;
+
+        let fee = calculate_fee_at_rate(gross_amount, fee_bps)?;
+        Ok((gross_amount, fee))
+    }
+
+    // 买入精确份额（bet_type 为结果索引），返回扣除的含费总额
+    pub fn buy_shares(&mut self, bet_type: u64, shares: u64, fee_bps: u64) -> Result<u64, u32> {
+        let (cost, fee) = self.calculate_cost(bet_type, shares, fee_bps)?;
+        let net_amount = safe_sub(cost, fee)?;
+        let index = bet_type as usize;
+
+        // 安全更新 AMM 定价状态
+        let (new_liquidity, _) = self.pricing.engine().cost(&self.liquidity, index, shares)?;
+        self.liquidity = new_liquidity;
+        self.total_shares[index] = safe_add(self.total_shares[index], shares)?;
+
+        // 安全更新状态
+        self.prize_pool = safe_add(self.prize_pool, net_amount)?;
+        self.total_volume = safe_add(self.total_volume, cost)?;
+        self.collect_fee(fee)?;
+
+        Ok(cost)
+    }
 
-        if shares_to_sell > total_shares {
+
+
+    // 统一的卖出函数（sell_type 为结果索引，二元市场 1=YES, 0=NO）
+    pub fn sell_shares(&mut self, sell_type: u64, shares_to_sell: u64, fee_bps: u64) -> Result<u64, u32> {
+        validate_shares(shares_to_sell)?;
+        let index = self.validate_outcome(sell_type)?;
+
+        if shares_to_sell > self.total_shares[index] {
             return Err(ERROR_INSUFFICIENT_BALANCE);
         }
 
         // 使用优化的计算函数，一次性计算净收益和费用
-        let (payout, fee) = self.calculate_sell_details(sell_type, shares_to_sell)?;
+        let (payout, fee) = self.calculate_sell_details(sell_type, shares_to_sell, fee_bps)?;
         if payout == 0 {
             return Err(ERROR_INVALID_BET_AMOUNT);
         }
@@ -268,21 +675,14 @@ impl MarketData {
             return Err(ERROR_INSUFFICIENT_BALANCE);
         }
 
-        // 安全更新 AMM 流动性
-        let k = calculate_k_safe(self.yes_liquidity, self.no_liquidity)?;
-        if is_yes_sell {
-            self.yes_liquidity = safe_add(self.yes_liquidity, shares_to_sell)?;
-            self.no_liquidity = calculate_new_liquidity_safe(k, self.yes_liquidity)?;
-            self.total_yes_shares = safe_sub(self.total_yes_shares, shares_to_sell)?;
-        } else {
-            self.no_liquidity = safe_add(self.no_liquidity, shares_to_sell)?;
-            self.yes_liquidity = calculate_new_liquidity_safe(k, self.no_liquidity)?;
-            self.total_no_shares = safe_sub(self.total_no_shares, shares_to_sell)?;
-        }
+        // 安全更新 AMM 定价状态
+        let (new_liquidity, _) = self.pricing.engine().sell(&self.liquidity, index, shares_to_sell)?;
+        self.liquidity = new_liquidity;
+        self.total_shares[index] = safe_sub(self.total_shares[index], shares_to_sell)?;
 
         // 安全更新状态
         self.prize_pool = safe_sub(self.prize_pool, payout)?;
-        self.total_fees_collected = safe_add(self.total_fees_collected, fee)?;
+        self.collect_fee(fee)?;
         // 将卖出金额（payout + fee）计入总交易量
         let total_transaction_value = safe_add(payout, fee)?;
         self.total_volume = safe_add(self.total_volume, total_transaction_value)?;
@@ -292,42 +692,264 @@ impl MarketData {
 
 
 
-    // 市场解决
-    pub fn resolve(&mut self, outcome: bool) -> Result<(), u32> {
-        if self.resolved {
-            return Err(ERROR_MARKET_ALREADY_RESOLVED);
+    // 交易费用按市场的分配比例分给流动性提供者和创建者，其余（包括无人领取的推荐人部分）归平台
+    fn collect_fee(&mut self, fee: u64) -> Result<(), u32> {
+        let lp_fee = if self.total_lp_shares > 0 {
+            FeeSplit::share(fee, self.fee_split.lp_bps)
+        } else {
+            0
+        };
+        let creator_fee = FeeSplit::share(fee, self.fee_split.creator_bps);
+        let referrer_fee = FeeSplit::share(fee, self.fee_split.referrer_bps);
+        self.lp_fees = safe_add(self.lp_fees, lp_fee)?;
+        self.creator_fees = safe_add(self.creator_fees, creator_fee)?;
+        self.referrer_fees = safe_add(self.referrer_fees, referrer_fee)?;
+        self.total_fees_collected = safe_add(self.total_fees_collected, fee - lp_fee - creator_fee - referrer_fee)?;
+        Ok(())
+    }
+
+    // 结算一笔费用中的推荐人部分：有推荐人时取出交给调用方记入推荐人，否则归入平台
+    pub fn settle_referrer_share(&mut self, fee: u64, referred: bool) -> Result<u64, u32> {
+        let rebate = FeeSplit::share(fee, self.fee_split.referrer_bps);
+        self.referrer_fees = safe_sub(self.referrer_fees, rebate)?;
+        if referred {
+            return Ok(rebate);
         }
-        
-        self.resolved = true;
-        self.outcome = Some(outcome);
+        self.total_fees_collected = safe_add(self.total_fees_collected, rebate)?;
+        Ok(0)
+    }
+
+    // 按新旧深度等比缩放定价状态，价格保持不变
+    fn scale_depth(&mut self, old_depth: u64, new_depth: u64) -> Result<(), u32> {
+        let mut state = Vec::with_capacity(self.liquidity.len());
+        for &value in self.liquidity.iter() {
+            state.push(safe_div_high_precision(value, new_depth, old_depth)?);
+        }
+        let pricing = self.pricing.scaled(new_depth, old_depth)?;
+        pricing.engine().validate_state(&state)?;
+
+        self.liquidity = state;
+        self.pricing = pricing;
         Ok(())
     }
 
-    // 安全计算奖金
-    pub fn calculate_payout(&self, yes_shares: u64, no_shares: u64) -> Result<u64, u32> {
-        if !self.resolved || self.prize_pool == 0 {
-            return Ok(0);
+    // 注入流动性：按资产净值（份额组按面值 + 累计费用）铸造 LP 份额，之前交易的费用不会分给新 LP；
+    // 抵押品作为 LP 池持有的完整份额组进入奖池，与交易者一起承担结算盈亏，深度增加注入额
+    pub fn add_liquidity(&mut self, amount: u64) -> Result<u64, u32> {
+        validate_liquidity(amount)?;
+        if self.is_resolved() {
+            return Err(ERROR_MARKET_ALREADY_RESOLVED);
+        }
+
+        let lp_shares = if self.total_lp_shares == 0 {
+            amount
+        } else {
+            let lp_value = safe_add(self.lp_pool, self.lp_fees)?;
+            safe_div_high_precision(amount, self.total_lp_shares, lp_value)?
+        };
+        if lp_shares == 0 {
+            return Err(ERROR_INVALID_BET_AMOUNT);
         }
 
-        match self.outcome {
-            Some(true) => {
-                // YES 获胜
-                if self.total_yes_shares == 0 {
-                    return Ok(0);
+        let depth = self.pricing.engine().depth(&self.liquidity);
+        self.scale_depth(depth, safe_add(depth, amount)?)?;
+
+        for shares in self.total_shares.iter_mut() {
+            *shares = safe_add(*shares, amount)?;
+        }
+        self.prize_pool = safe_add(self.prize_pool, amount)?;
+        self.add_cost_basis(amount)?;
+
+        self.lp_pool = safe_add(self.lp_pool, amount)?;
+        self.total_lp_shares = safe_add(self.total_lp_shares, lp_shares)?;
+        Ok(lp_shares)
+    }
+
+    // LP 池持有的 sets 组份额在结算后的价值：无效时按成本退款，否则与交易者一样按份额分配奖池
+    pub fn lp_settlement_value(&self, sets: u64) -> Result<u64, u32> {
+        if self.invalid {
+            self.calculate_refund(sets)
+        } else {
+            self.calculate_payout(&vec![sets; self.liquidity.len()])
+        }
+    }
+
+    // 赎回流动性，按份额比例附带费用：交易期内按面值赎回份额组并减少深度，结果生效后按结算价值支付；
+    // 交易结束到结果生效之间不能退出，LP 承担结算结果
+    pub fn remove_liquidity(&mut self, lp_shares: u64, current_time: u64) -> Result<u64, u32> {
+        if lp_shares == 0 {
+            return Err(ERROR_INVALID_BET_AMOUNT);
+        }
+        if lp_shares > self.total_lp_shares {
+            return Err(ERROR_INSUFFICIENT_BALANCE);
+        }
+
+        let sets = safe_div_high_precision(self.lp_pool, lp_shares, self.total_lp_shares)?;
+        let fees = safe_div_high_precision(self.lp_fees, lp_shares, self.total_lp_shares)?;
+
+        let collateral = match self.status_at(current_time) {
+            MarketStatus::Scheduled | MarketStatus::Open => {
+                let depth = self.pricing.engine().depth(&self.liquidity);
+                if sets >= depth {
+                    return Err(ERROR_INVALID_CALCULATION);
                 }
-                safe_div_high_precision(yes_shares, self.prize_pool, self.total_yes_shares)
-            },
-            Some(false) => {
-                // NO 获胜
-                if self.total_no_shares == 0 {
-                    return Ok(0);
+                self.scale_depth(depth, depth - sets)?;
+
+                // 份额组按面值赎回，卖出已耗尽奖池时需等到结算
+                self.prize_pool = safe_sub(self.prize_pool, sets).map_err(|_| ERROR_INSUFFICIENT_BALANCE)?;
+                for shares in self.total_shares.iter_mut() {
+                    *shares = safe_sub(*shares, sets)?;
                 }
-                safe_div_high_precision(no_shares, self.prize_pool, self.total_no_shares)
+                self.remove_cost_basis(sets)?;
+                sets
             },
-            None => Ok(0),
+            // 结算后奖池与总份额保持不变，其余 LP 和交易者按同样的比例领取
+            MarketStatus::Finalized | MarketStatus::Cancelled => self.lp_settlement_value(sets)?,
+            _ => return Err(ERROR_MARKET_NOT_RESOLVED),
+        };
+
+        self.lp_pool = safe_sub(self.lp_pool, sets)?;
+        self.lp_fees = safe_sub(self.lp_fees, fees)?;
+        self.total_lp_shares = safe_sub(self.total_lp_shares, lp_shares)?;
+        safe_add(collateral, fees)
+    }
+
+    // 记录交易者和 LP 池的净投入成本，用于无效结算时退款
+    pub fn add_cost_basis(&mut self, amount: u64) -> Result<(), u32> {
+        self.total_cost_basis = safe_add(self.total_cost_basis, amount)?;
+        Ok(())
+    }
+
+    pub fn remove_cost_basis(&mut self, amount: u64) -> Result<(), u32> {
+        self.total_cost_basis = safe_sub(self.total_cost_basis, amount)?;
+        Ok(())
+    }
+
+    // 校验解决结果（outcome 为获胜结果索引，标量市场为解决值，OUTCOME_INVALID 表示无效）
+    pub(crate) fn settle_outcome(&self, outcome: u64) -> Result<Option<u64>, u32> {
+        if outcome == OUTCOME_INVALID {
+            return Ok(None);
+        }
+        match self.scalar {
+            Some(range) => Ok(Some(outcome.clamp(range.lower, range.upper))),
+            None if outcome >= self.outcome_count() => Err(ERROR_INVALID_OUTCOME),
+            None => Ok(Some(outcome)),
         }
     }
 
+    // 市场解决，跳过状态机与争议期立即生效，仅供测试构造已结算的市场
+    #[cfg(test)]
+    pub fn resolve(&mut self, outcome: u64) -> Result<(), u32> {
+        if self.is_resolved() {
+            return Err(ERROR_MARKET_ALREADY_RESOLVED);
+        }
+
+        self.outcome = self.settle_outcome(outcome)?;
+        self.invalid = self.outcome.is_none();
+        self.status = MarketStatus::Finalized;
+        Ok(())
+    }
+
+    // 当前解决结果（无效为 OUTCOME_INVALID），未解决时为 None
+    pub fn resolved_outcome(&self) -> Option<u64> {
+        match (self.is_resolved(), self.invalid) {
+            (false, _) => None,
+            (true, true) => Some(OUTCOME_INVALID),
+            (true, false) => self.outcome,
+        }
+    }
+
+    // 结果已过争议期，可以领取
+    pub fn is_finalized(&self, current_time: u64) -> bool {
+        matches!(self.status_at(current_time), MarketStatus::Finalized | MarketStatus::Cancelled)
+    }
+
+    // 提出或更正解决结果（Closed / Proposed -> Proposed，重新开始争议期；Disputed -> Finalized）
+    pub fn propose_resolution(&mut self, outcome: u64, current_time: u64) -> Result<(), u32> {
+        let status = self.status_at(current_time);
+        match status {
+            MarketStatus::Finalized | MarketStatus::Cancelled => return Err(ERROR_MARKET_ALREADY_RESOLVED),
+            _ if !self.can_resolve(current_time) => return Err(ERROR_MARKET_NOT_ENDED),
+            _ => (),
+        }
+
+        self.outcome = self.settle_outcome(outcome)?;
+        self.invalid = self.outcome.is_none();
+        if status == MarketStatus::Disputed {
+            self.status = MarketStatus::Finalized;
+            self.dispute_end = current_time;
+        } else {
+            self.status = MarketStatus::Proposed;
+            self.dispute_end = safe_add(current_time, DISPUTE_PERIOD_TICKS)?;
+        }
+        Ok(())
+    }
+
+    // 争议期内质疑提出的结果（Proposed -> Disputed），等待管理员裁定
+    pub fn dispute(&mut self, current_time: u64) -> Result<(), u32> {
+        if self.status_at(current_time) != MarketStatus::Proposed {
+            return Err(ERROR_DISPUTE_WINDOW_CLOSED);
+        }
+        self.status = MarketStatus::Disputed;
+        Ok(())
+    }
+
+    // 开始前取消市场（Scheduled -> Cancelled），按无效结算处理
+    pub fn cancel(&mut self, current_time: u64) -> Result<(), u32> {
+        match self.status_at(current_time) {
+            MarketStatus::Scheduled => (),
+            _ if self.is_resolved() => return Err(ERROR_MARKET_ALREADY_RESOLVED),
+            _ => return Err(ERROR_MARKET_ALREADY_STARTED),
+        }
+        self.outcome = None;
+        self.invalid = true;
+        self.status = MarketStatus::Cancelled;
+        Ok(())
+    }
+
+    // 无效结算退款：返还净投入成本，奖池不足时按比例分配
+    pub fn calculate_refund(&self, cost_basis: u64) -> Result<u64, u32> {
+        if !self.invalid || self.total_cost_basis == 0 || self.prize_pool == 0 {
+            return Ok(0);
+        }
+        if self.prize_pool >= self.total_cost_basis {
+            return Ok(cost_basis);
+        }
+        safe_div_high_precision(cost_basis, self.prize_pool, self.total_cost_basis)
+    }
+
+    // 安全计算奖金（shares[i] 为持有的结果 i 份额）
+    pub fn calculate_payout(&self, shares: &[u64]) -> Result<u64, u32> {
+        if !self.is_resolved() || self.prize_pool == 0 {
+            return Ok(0);
+        }
+
+        match (self.outcome, self.scalar) {
+            (Some(value), Some(range)) => {
+                // 奖池按比例分成 LONG 与 SHORT 两部分，各自按份额分配
+                let long_ratio = range.long_ratio(value)?;
+                let long_pool = safe_div_high_precision(long_ratio, self.prize_pool, PRICE_PRECISION)?;
+                let short_pool = safe_sub(self.prize_pool, long_pool)?;
+                let long_payout = self.pool_share(shares, OUTCOME_LONG, long_pool)?;
+                let short_payout = self.pool_share(shares, OUTCOME_SHORT, short_pool)?;
+                safe_add(long_payout, short_payout)
+            },
+            (Some(winner), None) => self.pool_share(shares, winner, self.prize_pool),
+            (None, _) => Ok(0),
+        }
+    }
+
+    // 按持有的结果份额占该结果总份额的比例分配 pool
+    fn pool_share(&self, shares: &[u64], outcome: u64, pool: u64) -> Result<u64, u32> {
+        let index = outcome as usize;
+        let total_outcome_shares = self.total_shares[index];
+        if total_outcome_shares == 0 || pool == 0 {
+            return Ok(0);
+        }
+        let outcome_shares = shares.get(index).copied().unwrap_or(0);
+        safe_div_high_precision(outcome_shares, pool, total_outcome_shares)
+    }
+
     // // 获取份额价值（解决前估算）- 前端分析用，后端不使用
     // pub fn get_share_value(&self, is_yes_share: bool) -> Result<u64, u32> {
     //     if self.prize_pool == 0 {
@@ -346,28 +968,137 @@ impl MarketData {
     // }
 }
 
+// Markets are stored under [MARKET_KEY_PREFIX, market_id, 0, 0], next to the global state at [0, 0, 0, 0]
+const MARKET_KEY_PREFIX: u64 = 1;
+// Market text lives beside the market under [MARKET_KEY_PREFIX, market_id, MARKET_TEXT_KEY, 0]
+const MARKET_TEXT_KEY: u64 = 1;
+// Resting limit orders live under [MARKET_KEY_PREFIX, market_id, MARKET_ORDERS_KEY, 0]
+const MARKET_ORDERS_KEY: u64 = 2;
+// The bond behind a dispute of a proposed outcome lives under [MARKET_KEY_PREFIX, market_id, MARKET_DISPUTE_KEY, 0]
+const MARKET_DISPUTE_KEY: u64 = 3;
+// The resolver committee lives under [MARKET_KEY_PREFIX, market_id, MARKET_RESOLVERS_KEY, 0]
+const MARKET_RESOLVERS_KEY: u64 = 4;
+// A bonded resolution proposal lives under [MARKET_KEY_PREFIX, market_id, MARKET_PROPOSAL_KEY, 0]
+const MARKET_PROPOSAL_KEY: u64 = 5;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Market {
+    pub market_id: u64,
+    pub data: MarketData,
+}
+
+impl Market {
+    fn to_key(market_id: u64) -> [u64; 4] {
+        [MARKET_KEY_PREFIX, market_id, 0, 0]
+    }
+
+    fn to_text_key(market_id: u64) -> [u64; 4] {
+        [MARKET_KEY_PREFIX, market_id, MARKET_TEXT_KEY, 0]
+    }
+
+    pub fn to_orders_key(market_id: u64) -> [u64; 4] {
+        [MARKET_KEY_PREFIX, market_id, MARKET_ORDERS_KEY, 0]
+    }
+
+    pub fn to_dispute_key(market_id: u64) -> [u64; 4] {
+        [MARKET_KEY_PREFIX, market_id, MARKET_DISPUTE_KEY, 0]
+    }
+
+    pub fn to_resolvers_key(market_id: u64) -> [u64; 4] {
+        [MARKET_KEY_PREFIX, market_id, MARKET_RESOLVERS_KEY, 0]
+    }
+
+    pub fn to_proposal_key(market_id: u64) -> [u64; 4] {
+        [MARKET_KEY_PREFIX, market_id, MARKET_PROPOSAL_KEY, 0]
+    }
+
+    pub fn get(market_id: u64) -> Option<Self> {
+        let kvpair = unsafe { &mut MERKLE_MAP };
+        let mut data = kvpair.get(&Self::to_key(market_id));
+        if data.is_empty() {
+            None
+        } else {
+            let mut u64data = data.iter_mut();
+            let mut market = MarketData::from_data(&mut u64data);
+            let mut text = kvpair.get(&Self::to_text_key(market_id));
+            if !text.is_empty() {
+                market.text = MarketText::from_data(&mut text.iter_mut());
+            }
+            Some(Market {
+                market_id,
+                data: market,
+            })
+        }
+    }
+
+    // Text is immutable, so trades only rewrite the numeric state
+    pub fn store(&self) {
+        let mut data = vec![];
+        self.data.to_data(&mut data);
+        let kvpair = unsafe { &mut MERKLE_MAP };
+        kvpair.set(&Self::to_key(self.market_id), data.as_slice());
+    }
+
+    pub fn store_text(&self) {
+        let mut data = vec![];
+        self.data.text.to_data(&mut data);
+        let kvpair = unsafe { &mut MERKLE_MAP };
+        kvpair.set(&Self::to_text_key(self.market_id), data.as_slice());
+    }
+}
+
 impl StorageData for MarketData {
     fn from_data(u64data: &mut std::slice::IterMut<u64>) -> Self {
+        let start_time = *u64data.next().unwrap();
+        let end_time = *u64data.next().unwrap();
+        let resolution_time = *u64data.next().unwrap();
+        let outcome_count = *u64data.next().unwrap() as usize;
+        let liquidity = (0..outcome_count).map(|_| *u64data.next().unwrap()).collect();
+        let pricing = PricingModel::from_data(u64data);
+        let prize_pool = *u64data.next().unwrap();
+        let total_volume = *u64data.next().unwrap();
+        let total_shares = (0..outcome_count).map(|_| *u64data.next().unwrap()).collect();
+        let status = MarketStatus::from_code(*u64data.next().unwrap());
+        // 0 = unresolved, i + 1 = outcome i wins, OUTCOME_INVALID = invalid
+        let (outcome, invalid) = match *u64data.next().unwrap() {
+            0 => (None, false),
+            OUTCOME_INVALID => (None, true),
+            outcome_val => (Some(outcome_val - 1), false),
+        };
         MarketData {
-            title: "Prediction Market".to_string(),
-            description: "Will the event happen?".to_string(),
-            start_time: *u64data.next().unwrap(),
-            end_time: *u64data.next().unwrap(),
-            resolution_time: *u64data.next().unwrap(),
-            yes_liquidity: *u64data.next().unwrap(),
-            no_liquidity: *u64data.next().unwrap(),
-            prize_pool: *u64data.next().unwrap(),
-            total_volume: *u64data.next().unwrap(),
-            total_yes_shares: *u64data.next().unwrap(),
-            total_no_shares: *u64data.next().unwrap(),
-            resolved: *u64data.next().unwrap() != 0,
-            outcome: {
-                let outcome_val = *u64data.next().unwrap();
-                if outcome_val == 0 { None }
-                else if outcome_val == 1 { Some(false) }
-                else { Some(true) }
-            },
+            // Filled in from the text key by Market::get
+            text: MarketText::default(),
+            start_time,
+            end_time,
+            resolution_time,
+            liquidity,
+            pricing,
+            prize_pool,
+            total_volume,
+            total_shares,
+            status,
+            outcome,
+            invalid,
+            dispute_end: *u64data.next().unwrap(),
             total_fees_collected: *u64data.next().unwrap(),
+            lp_pool: *u64data.next().unwrap(),
+            lp_fees: *u64data.next().unwrap(),
+            total_lp_shares: *u64data.next().unwrap(),
+            total_cost_basis: *u64data.next().unwrap(),
+            scalar: match *u64data.next().unwrap() {
+                0 => None,
+                _ => Some(ScalarRange {
+                    lower: *u64data.next().unwrap(),
+                    upper: *u64data.next().unwrap(),
+                }),
+            },
+            pause: Pause::from_code(*u64data.next().unwrap()),
+            breaker: CircuitBreaker::from_data(u64data),
+            fee_schedule: FeeSchedule::from_data(u64data),
+            fee_split: FeeSplit::from_data(u64data),
+            creator: [*u64data.next().unwrap(), *u64data.next().unwrap()],
+            creator_fees: *u64data.next().unwrap(),
+            referrer_fees: *u64data.next().unwrap(),
         }
     }
 
@@ -375,18 +1106,35 @@ impl StorageData for MarketData {
         data.push(self.start_time);
         data.push(self.end_time);
         data.push(self.resolution_time);
-        data.push(self.yes_liquidity);
-        data.push(self.no_liquidity);
+        data.push(self.liquidity.len() as u64);
+        data.extend_from_slice(&self.liquidity);
+        self.pricing.to_data(data);
         data.push(self.prize_pool);
         data.push(self.total_volume);
-        data.push(self.total_yes_shares);
-        data.push(self.total_no_shares);
-        data.push(if self.resolved { 1 } else { 0 });
+        data.extend_from_slice(&self.total_shares);
+        data.push(self.status.code());
         data.push(match self.outcome {
+            _ if self.invalid => OUTCOME_INVALID,
             None => 0,
-            Some(false) => 1,
-            Some(true) => 2,
+            Some(outcome) => outcome + 1,
         });
+        data.push(self.dispute_end);
         data.push(self.total_fees_collected);
+        data.push(self.lp_pool);
+        data.push(self.lp_fees);
+        data.push(self.total_lp_shares);
+        data.push(self.total_cost_basis);
+        match self.scalar {
+            None => data.push(0),
+            Some(range) => data.extend_from_slice(&[1, range.lower, range.upper]),
+        }
+        data.push(self.pause.code());
+        self.breaker.to_data(data);
+        self.fee_schedule.to_data(data);
+        self.fee_split.to_data(data);
+        data.push(self.creator[0]);
+        data.push(self.creator[1]);
+        data.push(self.creator_fees);
+        data.push(self.referrer_fees);
     }
-} 
\ No newline at end of file
+}
@@ -1,3 +1,4 @@
+use primitive_types::U512;
 use crate::error::*;
 use crate::config::{PRICE_PRECISION, FEE_BASIS_POINTS, PLATFORM_FEE_RATE};
 
@@ -6,6 +7,8 @@ pub const MAX_LIQUIDITY: u64 = 1_000_000_000_000;  // 1万亿 - 防止溢出
 pub const MAX_BET_AMOUNT: u64 = 100_000_000;       // 1亿 - 合理的最大投注
 pub const MIN_LIQUIDITY: u64 = 1000;               // 最小流动性 - 防止除零
 pub const MAX_SHARES: u64 = 1_000_000_000;         // 最大份额数
+pub const MIN_OUTCOMES: usize = 2;                 // 二元市场
+pub const MAX_OUTCOMES: usize = 10;                // 10 个 40 位流动性的乘积仍在 U512 范围内
 
 /// 安全的乘法运算，检查溢出
 pub fn safe_mul(a: u64, b: u64) -> Result<u64, u32> {
@@ -100,16 +103,232 @@ pub fn calculate_new_liquidity_safe(k: u128, other_liquidity: u64) -> Result<u64
     Ok(result)
 }
 
+/// 安全计算多结果 AMM 常量乘积 k = r_0 * r_1 * ... * r_n
+pub fn calculate_k_multi_safe(reserves: &[u64]) -> Result<U512, u32> {
+    if reserves.len() < MIN_OUTCOMES || reserves.len() > MAX_OUTCOMES {
+        return Err(ERROR_INVALID_OUTCOME);
+    }
+
+    let mut k = U512::one();
+    for &reserve in reserves {
+        validate_liquidity(reserve)?;
+        k = k.checked_mul(U512::from(reserve)).ok_or(ERROR_OVERFLOW)?;
+    }
+
+    Ok(k)
+}
+
+/// 安全计算多结果 AMM 中某一结果的新流动性 k / Π(其他结果流动性)
+/// 二元市场时与 calculate_new_liquidity_safe 完全一致
+pub fn calculate_new_liquidity_multi_safe(k: U512, others: &[u64]) -> Result<u64, u32> {
+    let mut denominator = U512::one();
+    for &reserve in others {
+        if reserve == 0 {
+            return Err(ERROR_DIVISION_BY_ZERO);
+        }
+        denominator = denominator.checked_mul(U512::from(reserve)).ok_or(ERROR_OVERFLOW)?;
+    }
+
+    let new_liquidity = k / denominator;
+
+    if new_liquidity > U512::from(u64::MAX) {
+        return Err(ERROR_OVERFLOW);
+    }
+
+    let result = new_liquidity.low_u64();
+
+    // 确保结果不会太小或太大
+    if !(MIN_LIQUIDITY..=MAX_LIQUIDITY).contains(&result) {
+        return Err(ERROR_INVALID_CALCULATION);
+    }
+
+    Ok(result)
+}
+
+/// 安全计算卖出时其他结果流动性的统一减少量：满足 Π(r_i - y) >= target 的最大 y
+/// 二元市场时 y = r - target，与原有卖出公式一致
+pub fn calculate_uniform_withdrawal_safe(target: U512, others: &[u64]) -> Result<u64, u32> {
+    let min_reserve = *others.iter().min().ok_or(ERROR_INVALID_CALCULATION)?;
+
+    let product_after = |y: u64| -> Result<U512, u32> {
+        let mut product = U512::one();
+        for &reserve in others {
+            product = product.checked_mul(U512::from(reserve - y)).ok_or(ERROR_OVERFLOW)?;
+        }
+        Ok(product)
+    };
+
+    if product_after(0)? < target {
+        return Err(ERROR_INVALID_CALCULATION);
+    }
+
+    // 二分查找，Π(r_i - y) 随 y 单调递减
+    let mut low = 0;
+    let mut high = min_reserve;
+    while low < high {
+        let mid = low + (high - low).div_ceil(2);
+        if product_after(mid)? >= target {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+
+    Ok(low)
+}
+
+/// 安全计算最小统一存入量：满足 Π(r_i + x) >= target 的最小 x（买入精确份额用）
+pub fn calculate_uniform_deposit_safe(target: U512, others: &[u64]) -> Result<u64, u32> {
+    let product_after = |x: u64| -> Result<U512, u32> {
+        let mut product = U512::one();
+        for &reserve in others {
+            let reserve = reserve.checked_add(x).ok_or(ERROR_OVERFLOW)?;
+            product = product.checked_mul(U512::from(reserve)).ok_or(ERROR_OVERFLOW)?;
+        }
+        Ok(product)
+    };
+
+    if product_after(MAX_BET_AMOUNT)? < target {
+        return Err(ERROR_BET_TOO_LARGE);
+    }
+
+    // 二分查找，Π(r_i + x) 随 x 单调递增
+    let mut low = 0;
+    let mut high = MAX_BET_AMOUNT;
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if product_after(mid)? >= target {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+
+    Ok(low)
+}
+
+/// 安全计算多结果价格 p_i = Π_{k≠i} r_k / Σ_m Π_{k≠m} r_k（所有结果价格之和为 1）
+pub fn calculate_outcome_price_safe(reserves: &[u64], index: usize) -> Result<u64, u32> {
+    if index >= reserves.len() {
+        return Err(ERROR_INVALID_BET_TYPE);
+    }
+
+    let product_without = |skip: usize| -> Result<U512, u32> {
+        let mut product = U512::one();
+        for (i, &reserve) in reserves.iter().enumerate() {
+            if i != skip {
+                product = product.checked_mul(U512::from(reserve)).ok_or(ERROR_OVERFLOW)?;
+            }
+        }
+        Ok(product)
+    };
+
+    let mut denominator = U512::zero();
+    for m in 0..reserves.len() {
+        denominator = denominator.checked_add(product_without(m)?).ok_or(ERROR_OVERFLOW)?;
+    }
+
+    if denominator.is_zero() {
+        return Ok(PRICE_PRECISION / reserves.len() as u64); // 无流动性时均分
+    }
+
+    let numerator = product_without(index)?
+        .checked_mul(U512::from(PRICE_PRECISION))
+        .ok_or(ERROR_OVERFLOW)?;
+
+    Ok((numerator / denominator).low_u64())
+}
+
+// LMSR 定点数常量（FIXED_ONE = 1.0）
+pub const FIXED_ONE: u128 = 1_000_000_000_000;
+pub const LN2_FIXED: i128 = 693_147_180_560;            // ln(2)
+pub const MAX_EXP_INPUT: i128 = 40 * FIXED_ONE as i128; // e^40 仍在 u128 范围内
+pub const MIN_EXP_INPUT: i128 = -40 * FIXED_ONE as i128; // 更小的输入在该精度下为 0
+
+/// 安全计算定点数 e^x（x 与结果均以 FIXED_ONE 为 1.0）
+pub fn exp_fixed_safe(x: i128) -> Result<u128, u32> {
+    if x > MAX_EXP_INPUT {
+        return Err(ERROR_OVERFLOW);
+    }
+    if x < MIN_EXP_INPUT {
+        return Ok(0);
+    }
+
+    // 区间约简：x = k * ln2 + r，|r| <= ln2 / 2
+    let half = if x >= 0 { LN2_FIXED / 2 } else { -LN2_FIXED / 2 };
+    let k = (x + half) / LN2_FIXED;
+    let r = x - k * LN2_FIXED;
+
+    // 泰勒展开 e^r
+    let one = FIXED_ONE as i128;
+    let mut sum = one;
+    let mut term = one;
+    let mut n = 1;
+    while term != 0 {
+        term = term * r / (n * one);
+        sum += term;
+        n += 1;
+    }
+
+    // MAX_EXP_INPUT 保证左移不会溢出
+    let sum = sum as u128;
+    if k >= 0 {
+        Ok(sum << k as u32)
+    } else {
+        Ok(sum >> (-k) as u32)
+    }
+}
+
+/// 安全计算定点数 ln(x)（x > 0，x 与结果均以 FIXED_ONE 为 1.0）
+pub fn ln_fixed_safe(x: u128) -> Result<i128, u32> {
+    if x == 0 {
+        return Err(ERROR_INVALID_CALCULATION);
+    }
+
+    // 归一化到 [1, 2)：x = y * 2^k
+    let mut k: i128 = 0;
+    let mut y = x;
+    while y >= 2 * FIXED_ONE {
+        y >>= 1;
+        k += 1;
+    }
+    while y < FIXED_ONE {
+        y <<= 1;
+        k -= 1;
+    }
+
+    // ln(y) = 2 * atanh(z)，z = (y - 1) / (y + 1) ∈ [0, 1/3)
+    let one = FIXED_ONE as i128;
+    let y = y as i128;
+    let z = (y - one) * one / (y + one);
+    let z_squared = z * z / one;
+    let mut sum = 0;
+    let mut term = z;
+    let mut n = 1;
+    while term != 0 {
+        sum += term / n;
+        term = term * z_squared / one;
+        n += 2;
+    }
+
+    Ok(2 * sum + k * LN2_FIXED)
+}
+
 /// 安全计算平台费用（向上取整确保不丢失费用）
 pub fn calculate_fee_safe(amount: u64) -> Result<u64, u32> {
+    calculate_fee_at_rate(amount, PLATFORM_FEE_RATE)
+}
+
+/// 按指定费率（基点）计算费用，向上取整
+pub fn calculate_fee_at_rate(amount: u64, fee_bps: u64) -> Result<u64, u32> {
     if amount > MAX_BET_AMOUNT {
         return Err(ERROR_BET_TOO_LARGE);
     }
     
-    // 计算 (amount * PLATFORM_FEE_RATE + FEE_BASIS_POINTS - 1) / FEE_BASIS_POINTS
+    // 计算 (amount * fee_bps + FEE_BASIS_POINTS - 1) / FEE_BASIS_POINTS
     // 这样可以实现向上取整
     let numerator = (amount as u128)
-        .checked_mul(PLATFORM_FEE_RATE as u128)
+        .checked_mul(fee_bps as u128)
         .ok_or(ERROR_OVERFLOW)?;
     
     let rounded_numerator = numerator
@@ -127,10 +346,42 @@ pub fn calculate_fee_safe(amount: u64) -> Result<u64, u32> {
 
 /// 安全计算净金额（扣除费用后）
 pub fn calculate_net_amount_safe(bet_amount: u64) -> Result<u64, u32> {
-    let fee = calculate_fee_safe(bet_amount)?;
+    calculate_net_amount_at_rate(bet_amount, PLATFORM_FEE_RATE)
+}
+
+/// 按指定费率计算净金额
+pub fn calculate_net_amount_at_rate(bet_amount: u64, fee_bps: u64) -> Result<u64, u32> {
+    let fee = calculate_fee_at_rate(bet_amount, fee_bps)?;
     safe_sub(bet_amount, fee)
 }
 
+/// 安全计算含费总额：扣除费用后净额不少于 net_amount 的最小总额
+pub fn calculate_gross_amount_safe(net_amount: u64) -> Result<u64, u32> {
+    calculate_gross_amount_at_rate(net_amount, PLATFORM_FEE_RATE)
+}
+
+/// 按指定费率（低于 100%）计算含费总额
+pub fn calculate_gross_amount_at_rate(net_amount: u64, fee_bps: u64) -> Result<u64, u32> {
+    // 费用向上取整，净额至多为总额的 (1 - 费率)，由此得到下界
+    let net_rate = safe_sub(FEE_BASIS_POINTS, fee_bps)?;
+    if net_rate == 0 {
+        return Err(ERROR_INVALID_CALCULATION);
+    }
+    let numerator = (net_amount as u128)
+        .checked_mul(FEE_BASIS_POINTS as u128)
+        .ok_or(ERROR_OVERFLOW)?;
+    let lower_bound = numerator.div_ceil(net_rate as u128);
+    if lower_bound > MAX_BET_AMOUNT as u128 {
+        return Err(ERROR_BET_TOO_LARGE);
+    }
+
+    let mut gross_amount = lower_bound as u64;
+    while calculate_net_amount_at_rate(gross_amount, fee_bps)? < net_amount {
+        gross_amount = safe_add(gross_amount, 1)?;
+    }
+    Ok(gross_amount)
+}
+
 /// 安全计算价格（防止精度丢失）
 pub fn calculate_price_safe(numerator: u64, denominator: u64) -> Result<u64, u32> {
     if denominator == 0 {
@@ -229,4 +480,35 @@ mod tests {
         assert_eq!(validate_bet_amount(0), Err(ERROR_INVALID_BET_AMOUNT));
         assert_eq!(validate_bet_amount(MAX_BET_AMOUNT + 1), Err(ERROR_BET_TOO_LARGE));
     }
+
+    #[test]
+    fn test_calculate_gross_amount_safe() {
+        for net_amount in [1, 99, 100, 9_900, 123_456] {
+            let gross_amount = calculate_gross_amount_safe(net_amount).unwrap();
+            assert!(calculate_net_amount_safe(gross_amount).unwrap() >= net_amount);
+            assert!(calculate_net_amount_safe(gross_amount - 1).unwrap() < net_amount);
+        }
+        assert_eq!(calculate_gross_amount_safe(MAX_BET_AMOUNT), Err(ERROR_BET_TOO_LARGE));
+    }
+
+    #[test]
+    fn test_exp_ln_fixed() {
+        let one = FIXED_ONE as i128;
+        assert_eq!(exp_fixed_safe(0), Ok(FIXED_ONE));
+        assert!(exp_fixed_safe(one).unwrap().abs_diff(2_718_281_828_459) < 10);
+        assert!(exp_fixed_safe(-one).unwrap().abs_diff(367_879_441_171) < 10);
+        assert_eq!(exp_fixed_safe(MAX_EXP_INPUT + 1), Err(ERROR_OVERFLOW));
+        assert_eq!(exp_fixed_safe(MIN_EXP_INPUT - 1), Ok(0));
+
+        assert_eq!(ln_fixed_safe(FIXED_ONE), Ok(0));
+        assert!((ln_fixed_safe(2 * FIXED_ONE).unwrap() - LN2_FIXED).abs() < 100);
+        assert!((ln_fixed_safe(FIXED_ONE / 10).unwrap() + 2_302_585_092_994).abs() < 100);
+        assert_eq!(ln_fixed_safe(0), Err(ERROR_INVALID_CALCULATION));
+
+        // ln(e^x) = x
+        for x in [-5 * one, -one / 3, one / 7, 5 * one, 30 * one] {
+            let roundtrip = ln_fixed_safe(exp_fixed_safe(x).unwrap()).unwrap();
+            assert!((roundtrip - x).abs() < 1000, "x = {}, ln(e^x) = {}", x, roundtrip);
+        }
+    }
 } 
\ No newline at end of file
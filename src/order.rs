@@ -0,0 +1,244 @@
+use serde::Serialize;
+use zkwasm_rest_abi::{StorageData, MERKLE_MAP};
+use crate::command::Activity;
+use crate::error::*;
+use crate::event::{insert_event, EVENT_ORDER_UPDATE};
+use crate::market::{Market, MarketData, MarketStatus};
+use crate::math_safe::calculate_fee_at_rate;
+use crate::player::Player;
+use crate::state::GLOBAL_STATE;
+
+// Bounds the work done for one market on every tick
+pub const MAX_ORDERS_PER_MARKET: usize = 32;
+
+// Order status reported in ORDER_UPDATE events
+pub const ORDER_PLACED: u64 = 0;
+pub const ORDER_FILLED: u64 = 1;
+pub const ORDER_CANCELLED: u64 = 2;
+pub const ORDER_PARTIALLY_FILLED: u64 = 3; // the rest of the order keeps resting
+
+#[derive(Serialize, Clone, Debug)]
+pub struct LimitOrder {
+    pub order_id: u64,
+    pub pid: [u64; 2],
+    pub outcome: u64,
+    pub limit_price: u64, // buy once the outcome price is at or below this (PRICE_PRECISION = 1.0)
+    pub amount: u64,      // collateral locked in the owner's PlayerData
+}
+
+impl LimitOrder {
+    pub fn is_triggered(&self, price: u64) -> bool {
+        price <= self.limit_price
+    }
+
+    // Fewest shares a fill of `amount` may return, so its average price, fee included, stays within the limit
+    pub fn min_shares_out(&self, market: &MarketData, amount: u64) -> Result<u64, u32> {
+        market.pricing.engine().min_shares_at_price(amount, self.limit_price)
+    }
+
+    /// Largest part of the order that fills within its limit at `fee_bps`; 0 if none does.
+    /// The average price only grows with the amount, so this bisects on it.
+    pub fn fillable_amount(&self, market: &MarketData, fee_bps: u64) -> u64 {
+        let within_limit = |amount: u64| {
+            let min_shares = self.min_shares_out(market, amount);
+            market.calculate_shares(self.outcome, amount, fee_bps)
+                .is_ok_and(|shares| min_shares.is_ok_and(|min_shares| shares > 0 && shares >= min_shares))
+        };
+        if within_limit(self.amount) {
+            return self.amount;
+        }
+        // low always fills within the limit (or is 0), high never does
+        let (mut low, mut high) = (0, self.amount);
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            if within_limit(mid) {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct OrderBook {
+    pub market_id: u64,
+    pub orders: Vec<LimitOrder>, // in placement order, which is also fill priority
+}
+
+impl OrderBook {
+    pub fn get(market_id: u64) -> Self {
+        let kvpair = unsafe { &mut MERKLE_MAP };
+        let mut data = kvpair.get(&Market::to_orders_key(market_id));
+        let orders = if data.is_empty() {
+            vec![]
+        } else {
+            let mut u64data = data.iter_mut();
+            let count = *u64data.next().unwrap();
+            (0..count).map(|_| LimitOrder::from_data(&mut u64data)).collect()
+        };
+        OrderBook { market_id, orders }
+    }
+
+    pub fn store(&self) {
+        let mut data = vec![self.orders.len() as u64];
+        for order in self.orders.iter() {
+            order.to_data(&mut data);
+        }
+        let kvpair = unsafe { &mut MERKLE_MAP };
+        kvpair.set(&Market::to_orders_key(self.market_id), data.as_slice());
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.orders.len() >= MAX_ORDERS_PER_MARKET
+    }
+
+    // Take out an order, only for its owner
+    pub fn remove(&mut self, order_id: u64, pid: &[u64; 2]) -> Result<LimitOrder, u32> {
+        let index = self.orders.iter()
+            .position(|o| o.order_id == order_id && o.pid == *pid)
+            .ok_or(ERROR_ORDER_NOT_EXIST)?;
+        Ok(self.orders.remove(index))
+    }
+}
+
+pub fn emit_order_event(order: &LimitOrder, market_id: u64, status: u64, shares: u64, fee_tier: u64, counter: u64) {
+    let mut data = vec![
+        order.order_id,
+        market_id,
+        order.pid[0],
+        order.pid[1],
+        order.outcome,
+        order.limit_price,
+        order.amount,
+        status,
+        shares,
+        counter,
+        fee_tier, // volume tier the fill was charged at
+    ];
+    insert_event(EVENT_ORDER_UPDATE, &mut data);
+}
+
+/// Match resting orders against the AMM; called once per tick.
+/// Orders fill at the AMM once the spot price crosses their limit, up to the amount whose
+/// average price stays within the limit, and are refunded once the market has ended or been resolved.
+pub fn match_limit_orders(counter: u64) {
+    let market_ids = GLOBAL_STATE.0.borrow().order_markets.clone();
+    for market_id in market_ids {
+        let mut market = match Market::get(market_id) {
+            Some(market) => market,
+            None => continue,
+        };
+        let status = market.data.status_at(counter);
+        let expired = !matches!(status, MarketStatus::Scheduled | MarketStatus::Open);
+        if status == MarketStatus::Scheduled {
+            continue; // not started yet
+        }
+        if !expired {
+            // Orders rest while the admin pause or the circuit breaker halts trading
+            if GLOBAL_STATE.0.borrow().is_trading_paused(&market.data) || market.data.breaker.is_halted(counter) {
+                continue;
+            }
+        }
+
+        let mut book = OrderBook::get(market_id);
+        let mut resting = Vec::with_capacity(book.orders.len());
+        let mut filled = false;
+        for order in book.orders.drain(..) {
+            let mut player = match Player::get_from_pid(&order.pid) {
+                Some(player) => player,
+                None => continue,
+            };
+
+            if expired {
+                player.data.unlock_balance(order.amount);
+                player.store();
+                emit_order_event(&order, market_id, ORDER_CANCELLED, 0, 0, counter);
+                continue;
+            }
+
+            // A fill that trips the circuit breaker leaves the rest of the book resting
+            let triggered = market.data.get_price(order.outcome)
+                .is_ok_and(|price| order.is_triggered(price));
+            if !triggered || market.data.breaker.is_halted(counter) {
+                resting.push(order);
+                continue;
+            }
+
+            // Fill against a copy so an order over a position limit leaves the market untouched
+            let mut data = market.data.clone();
+            let fee_tier = player.data.fee_tier();
+            let fill = data.trade_fee_bps(order.outcome, counter, fee_tier).and_then(|fee_bps| {
+                // A thin pool may only take part of the order within its limit
+                let amount = order.fillable_amount(&data, fee_bps);
+                if amount == 0 {
+                    return Err(ERROR_SLIPPAGE_EXCEEDED);
+                }
+                let shares = data.place_bet(order.outcome, amount, fee_bps)?;
+                let fee = calculate_fee_at_rate(amount, fee_bps)?;
+                player.data.record_trade(amount, fee)?;
+                player.data.add_shares(market_id, order.outcome, shares);
+                player.data.check_position_limit(market_id, order.outcome)?;
+                data.check_open_interest()?;
+                // Last, as the referrer is stored right away
+                Activity::pay_referrer(&player, &mut data, fee)?;
+                Ok((amount, shares))
+            });
+            match fill {
+                Ok((amount, shares)) => {
+                    market.data = data;
+                    player.data.spend_locked_balance(amount);
+                    player.data.add_outcome_cost_basis(market_id, order.outcome, amount);
+                    // Cannot overflow: the bet itself was added to the volume
+                    market.data.add_cost_basis(amount).unwrap();
+                    // Cannot fail for the same reason, and the cool-down fits in the tick counter
+                    Activity::check_circuit_breaker(&mut market, counter).unwrap();
+                    player.store();
+
+                    // Fill events report the filled amount; the rest of the order stays in the book
+                    let status = if amount < order.amount { ORDER_PARTIALLY_FILLED } else { ORDER_FILLED };
+                    let fill_order = LimitOrder { amount, ..order.clone() };
+                    emit_order_event(&fill_order, market_id, status, shares, fee_tier, counter);
+                    if amount < order.amount {
+                        resting.push(LimitOrder { amount: order.amount - amount, ..order });
+                    }
+                    filled = true;
+                }
+                // Keep the order resting if the AMM or the limits cannot take it right now
+                Err(_) => resting.push(order),
+            }
+        }
+        book.orders = resting;
+        book.store();
+
+        if filled {
+            market.store();
+            Activity::emit_market_event(&market, counter);
+        }
+        if book.orders.is_empty() {
+            GLOBAL_STATE.0.borrow_mut().untrack_order_market(market_id);
+        }
+    }
+}
+
+impl StorageData for LimitOrder {
+    fn from_data(u64data: &mut std::slice::IterMut<u64>) -> Self {
+        LimitOrder {
+            order_id: *u64data.next().unwrap(),
+            pid: [*u64data.next().unwrap(), *u64data.next().unwrap()],
+            outcome: *u64data.next().unwrap(),
+            limit_price: *u64data.next().unwrap(),
+            amount: *u64data.next().unwrap(),
+        }
+    }
+
+    fn to_data(&self, data: &mut Vec<u64>) {
+        data.push(self.order_id);
+        data.push(self.pid[0]);
+        data.push(self.pid[1]);
+        data.push(self.outcome);
+        data.push(self.limit_price);
+        data.push(self.amount);
+    }
+}
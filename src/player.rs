@@ -1,13 +1,55 @@
 use serde::Serialize;
 use zkwasm_rest_abi::StorageData;
+use crate::config::MAX_POSITION_SHARES;
 use crate::error::*;
+use crate::fee::fee_tier;
+use crate::math_safe::{safe_add, safe_div_high_precision};
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct MarketPosition {
+    pub market_id: u64,
+    pub shares: Vec<u64>, // Shares held per outcome index
+    pub claimed: bool,
+    pub lp_shares: u64, // Liquidity provider shares in the market's pool
+    pub cost_basis: u64, // Paid in minus received from sells, refunded if the market is invalid
+    // Minted complete sets still held; their shares of every outcome can only be redeemed as a set
+    pub set_shares: u64,
+    // Part of cost_basis paid for each outcome's free shares, moved pro rata with transferred shares;
+    // cost_basis - Σ outcome_cost_basis is the basis of the minted sets
+    pub outcome_cost_basis: Vec<u64>,
+}
+
+impl MarketPosition {
+    pub fn shares_of(&self, outcome: u64) -> u64 {
+        self.shares.get(outcome as usize).copied().unwrap_or(0)
+    }
+
+    // Shares of an outcome that can be sold or transferred, leaving out those of minted sets
+    pub fn free_shares_of(&self, outcome: u64) -> u64 {
+        self.shares_of(outcome).saturating_sub(self.set_shares)
+    }
+
+    // Holds shares of some outcome or LP shares, rather than an emptied position
+    pub fn has_stake(&self) -> bool {
+        self.lp_shares > 0 || self.shares.iter().any(|&shares| shares > 0)
+    }
+
+    pub fn outcome_cost_basis_of(&self, outcome: u64) -> u64 {
+        self.outcome_cost_basis.get(outcome as usize).copied().unwrap_or(0)
+    }
+}
 
 #[derive(Serialize, Clone, Debug, Default)]
 pub struct PlayerData {
     pub balance: u64,
-    pub yes_shares: u64,
-    pub no_shares: u64,
-    pub claimed: bool,
+    pub locked_balance: u64, // Collateral held by resting limit orders
+    pub traded_volume: u64,  // Cumulative bet, buy and sell amounts, sets the fee tier
+    pub fees_paid: u64,
+    pub referrer: Option<[u64; 2]>, // Set once, receives part of this player's trading fees
+    pub referral_count: u64,        // Players who named this player as their referrer
+    pub referral_earnings: u64,     // Fee rebates accrued from referred players, in total
+    pub referral_fees: u64,         // Accrued fee rebates not yet withdrawn to the balance
+    pub positions: Vec<MarketPosition>,
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -21,7 +63,7 @@ impl PredictionMarketPlayer {
     pub fn get(pkey: &[u64; 4]) -> Option<Self> {
         let player_id = Player::pkey_to_pid(pkey);
         let player = Player::get_from_pid(&player_id);
-        
+
         match player {
             Some(player) => Some(PredictionMarketPlayer {
                 player_id,
@@ -45,6 +87,50 @@ impl PlayerData {
         self.balance += amount;
     }
 
+    // Checked credit for balances arriving from other players
+    pub fn credit_balance(&mut self, amount: u64) -> Result<(), u32> {
+        self.balance = safe_add(self.balance, amount)?;
+        Ok(())
+    }
+
+    pub fn fee_tier(&self) -> u64 {
+        fee_tier(self.traded_volume)
+    }
+
+    // Count a trade towards the volume tiers
+    pub fn record_trade(&mut self, volume: u64, fee: u64) -> Result<(), u32> {
+        self.traded_volume = safe_add(self.traded_volume, volume)?;
+        self.fees_paid = safe_add(self.fees_paid, fee)?;
+        Ok(())
+    }
+
+    // A player can name one referrer, once, other than themselves
+    pub fn set_referrer(&mut self, player_id: &[u64; 2], referrer: [u64; 2]) -> Result<(), u32> {
+        if self.referrer.is_some() || referrer == *player_id {
+            return Err(ERROR_INVALID_REFERRER);
+        }
+        self.referrer = Some(referrer);
+        Ok(())
+    }
+
+    // Accrue a fee rebate from a referred player's trade
+    pub fn add_referral_earnings(&mut self, rebate: u64) -> Result<(), u32> {
+        self.referral_fees = safe_add(self.referral_fees, rebate)?;
+        self.referral_earnings = safe_add(self.referral_earnings, rebate)?;
+        Ok(())
+    }
+
+    // Move the accrued rebates to the balance, returning the amount withdrawn
+    pub fn withdraw_referral_fees(&mut self) -> Result<u64, u32> {
+        let fees = self.referral_fees;
+        if fees == 0 {
+            return Err(ERROR_NO_FEES_TO_WITHDRAW);
+        }
+        self.credit_balance(fees)?;
+        self.referral_fees = 0;
+        Ok(fees)
+    }
+
     pub fn spend_balance(&mut self, amount: u64) -> Result<(), u32> {
         if self.balance < amount {
             return Err(ERROR_INSUFFICIENT_BALANCE);
@@ -53,39 +139,250 @@ impl PlayerData {
         Ok(())
     }
 
-    pub fn add_yes_shares(&mut self, shares: u64) {
-        self.yes_shares += shares;
+    // Move balance into the locked bucket for a resting order
+    pub fn lock_balance(&mut self, amount: u64) -> Result<(), u32> {
+        self.spend_balance(amount)?;
+        self.locked_balance += amount;
+        Ok(())
+    }
+
+    pub fn unlock_balance(&mut self, amount: u64) {
+        self.locked_balance -= amount;
+        self.balance += amount;
+    }
+
+    pub fn spend_locked_balance(&mut self, amount: u64) {
+        self.locked_balance -= amount;
+    }
+
+    pub fn position(&self, market_id: u64) -> Option<&MarketPosition> {
+        self.positions.iter().find(|p| p.market_id == market_id)
+    }
+
+    // Get the position in a market, opening an empty one on first use
+    pub fn position_mut(&mut self, market_id: u64) -> &mut MarketPosition {
+        let index = match self.positions.iter().position(|p| p.market_id == market_id) {
+            Some(index) => index,
+            None => {
+                self.positions.push(MarketPosition {
+                    market_id,
+                    ..MarketPosition::default()
+                });
+                self.positions.len() - 1
+            }
+        };
+        &mut self.positions[index]
     }
 
-    pub fn add_no_shares(&mut self, shares: u64) {
-        self.no_shares += shares;
+    pub fn add_shares(&mut self, market_id: u64, outcome: u64, shares: u64) {
+        let position = self.position_mut(market_id);
+        let index = outcome as usize;
+        if position.shares.len() <= index {
+            position.shares.resize(index + 1, 0);
+        }
+        position.shares[index] += shares;
     }
 
-    pub fn claim_winnings(&mut self) -> Result<(), u32> {
-        if self.claimed {
+    // Checked after shares are added, so a trade cannot push a position over the limit
+    pub fn check_position_limit(&self, market_id: u64, outcome: u64) -> Result<(), u32> {
+        let shares = self.position(market_id).map_or(0, |p| p.shares_of(outcome));
+        if shares > MAX_POSITION_SHARES {
+            return Err(ERROR_POSITION_LIMIT_EXCEEDED);
+        }
+        Ok(())
+    }
+
+    // Minted sets are not priced by the AMM, so their shares never reach a sell or a transfer
+    pub fn spend_shares(&mut self, market_id: u64, outcome: u64, shares: u64) -> Result<(), u32> {
+        let position = self.position_mut(market_id);
+        if position.free_shares_of(outcome) < shares {
+            return Err(ERROR_INSUFFICIENT_BALANCE);
+        }
+        position.shares[outcome as usize] -= shares;
+        Ok(())
+    }
+
+    // Add one share of every outcome per minted set, held back from sells and transfers
+    pub fn add_set_shares(&mut self, market_id: u64, outcome_count: u64, sets: u64) {
+        for outcome in 0..outcome_count {
+            self.add_shares(market_id, outcome, sets);
+        }
+        self.position_mut(market_id).set_shares += sets;
+    }
+
+    // Burn one share of every outcome per redeemed set, releasing minted sets first
+    pub fn spend_set_shares(&mut self, market_id: u64, outcome_count: u64, sets: u64) -> Result<(), u32> {
+        let position = self.position_mut(market_id);
+        if (0..outcome_count).any(|outcome| position.shares_of(outcome) < sets) {
+            return Err(ERROR_INSUFFICIENT_BALANCE);
+        }
+        for shares in position.shares.iter_mut().take(outcome_count as usize) {
+            *shares -= sets;
+        }
+        position.set_shares -= sets.min(position.set_shares);
+        Ok(())
+    }
+
+    // Take shares out for a transfer, returning the part of the outcome's cost basis that moves with them
+    pub fn transfer_shares_out(&mut self, market_id: u64, outcome: u64, shares: u64) -> Result<u64, u32> {
+        let position = self.position_mut(market_id);
+        let held = position.free_shares_of(outcome);
+        let outcome_cost_basis = position.outcome_cost_basis_of(outcome);
+        self.spend_shares(market_id, outcome, shares)?;
+        let position = self.position_mut(market_id);
+        let cost_basis = safe_div_high_precision(outcome_cost_basis, shares, held)?;
+        if let Some(cost) = position.outcome_cost_basis.get_mut(outcome as usize) {
+            *cost -= cost_basis;
+        }
+        position.cost_basis -= cost_basis;
+        Ok(cost_basis)
+    }
+
+    pub fn add_lp_shares(&mut self, market_id: u64, lp_shares: u64) {
+        self.position_mut(market_id).lp_shares += lp_shares;
+    }
+
+    pub fn spend_lp_shares(&mut self, market_id: u64, lp_shares: u64) -> Result<(), u32> {
+        let position = self.position_mut(market_id);
+        if position.lp_shares < lp_shares {
+            return Err(ERROR_INSUFFICIENT_BALANCE);
+        }
+        position.lp_shares -= lp_shares;
+        Ok(())
+    }
+
+    pub fn add_cost_basis(&mut self, market_id: u64, amount: u64) {
+        self.position_mut(market_id).cost_basis += amount;
+    }
+
+    // Record the cost of shares bought in one outcome, in the total and in the outcome's part
+    pub fn add_outcome_cost_basis(&mut self, market_id: u64, outcome: u64, amount: u64) {
+        self.add_cost_basis(market_id, amount);
+        let position = self.position_mut(market_id);
+        let index = outcome as usize;
+        if position.outcome_cost_basis.len() <= index {
+            position.outcome_cost_basis.resize(index + 1, 0);
+        }
+        position.outcome_cost_basis[index] += amount;
+    }
+
+    // Reduce the cost basis by sale proceeds, returning the amount actually removed
+    pub fn reduce_cost_basis(&mut self, market_id: u64, amount: u64) -> u64 {
+        let position = self.position_mut(market_id);
+        let reduced = amount.min(position.cost_basis);
+        position.cost_basis -= reduced;
+        reduced
+    }
+
+    // Reduce the cost basis by the proceeds of selling one outcome, at most by that outcome's part,
+    // so other outcomes and minted sets keep their basis; returns the amount removed
+    pub fn reduce_outcome_cost_basis(&mut self, market_id: u64, outcome: u64, amount: u64) -> u64 {
+        let position = self.position_mut(market_id);
+        let cost = match position.outcome_cost_basis.get_mut(outcome as usize) {
+            Some(cost) => cost,
+            None => return 0,
+        };
+        let reduced = amount.min(*cost);
+        *cost -= reduced;
+        position.cost_basis -= reduced;
+        reduced
+    }
+
+    pub fn claim_winnings(&mut self, market_id: u64) -> Result<(), u32> {
+        let position = self.position_mut(market_id);
+        if position.claimed {
             return Err(ERROR_ALREADY_CLAIMED);
         }
-        self.claimed = true;
+        position.claimed = true;
         Ok(())
     }
 }
 
+impl StorageData for MarketPosition {
+    fn from_data(u64data: &mut std::slice::IterMut<u64>) -> Self {
+        let market_id = *u64data.next().unwrap();
+        let claimed = *u64data.next().unwrap() != 0;
+        let lp_shares = *u64data.next().unwrap();
+        let cost_basis = *u64data.next().unwrap();
+        let set_shares = *u64data.next().unwrap();
+        let outcome_count = *u64data.next().unwrap();
+        let shares = (0..outcome_count).map(|_| *u64data.next().unwrap()).collect();
+        let cost_count = *u64data.next().unwrap();
+        MarketPosition {
+            market_id,
+            shares,
+            claimed,
+            lp_shares,
+            cost_basis,
+            set_shares,
+            outcome_cost_basis: (0..cost_count).map(|_| *u64data.next().unwrap()).collect(),
+        }
+    }
+
+    fn to_data(&self, data: &mut Vec<u64>) {
+        data.push(self.market_id);
+        data.push(if self.claimed { 1 } else { 0 });
+        data.push(self.lp_shares);
+        data.push(self.cost_basis);
+        data.push(self.set_shares);
+        data.push(self.shares.len() as u64);
+        data.extend_from_slice(&self.shares);
+        data.push(self.outcome_cost_basis.len() as u64);
+        data.extend_from_slice(&self.outcome_cost_basis);
+    }
+}
+
 impl StorageData for PlayerData {
     fn from_data(u64data: &mut std::slice::IterMut<u64>) -> Self {
+        let balance = *u64data.next().unwrap();
+        let locked_balance = *u64data.next().unwrap();
+        let traded_volume = *u64data.next().unwrap();
+        let fees_paid = *u64data.next().unwrap();
+        let referrer = match *u64data.next().unwrap() {
+            0 => None,
+            _ => Some([*u64data.next().unwrap(), *u64data.next().unwrap()]),
+        };
+        let referral_count = *u64data.next().unwrap();
+        let referral_earnings = *u64data.next().unwrap();
+        let referral_fees = *u64data.next().unwrap();
+        let position_count = *u64data.next().unwrap();
+        let mut positions = Vec::with_capacity(position_count as usize);
+        for _ in 0..position_count {
+            positions.push(MarketPosition::from_data(u64data));
+        }
         PlayerData {
-            balance: *u64data.next().unwrap(),
-            yes_shares: *u64data.next().unwrap(),
-            no_shares: *u64data.next().unwrap(),
-            claimed: *u64data.next().unwrap() != 0,
+            balance,
+            locked_balance,
+            traded_volume,
+            fees_paid,
+            referrer,
+            referral_count,
+            referral_earnings,
+            referral_fees,
+            positions,
         }
     }
 
     fn to_data(&self, data: &mut Vec<u64>) {
         data.push(self.balance);
-        data.push(self.yes_shares);
-        data.push(self.no_shares);
-        data.push(if self.claimed { 1 } else { 0 });
+        data.push(self.locked_balance);
+        data.push(self.traded_volume);
+        data.push(self.fees_paid);
+        match self.referrer {
+            None => data.push(0),
+            Some(referrer) => {
+                data.push(1);
+                data.extend_from_slice(&referrer);
+            }
+        }
+        data.push(self.referral_count);
+        data.push(self.referral_earnings);
+        data.push(self.referral_fees);
+        data.push(self.positions.len() as u64);
+        for position in self.positions.iter() {
+            position.to_data(data);
+        }
     }
 }
 
-pub type Player = zkwasm_rest_abi::Player<PlayerData>; 
\ No newline at end of file
+pub type Player = zkwasm_rest_abi::Player<PlayerData>;
@@ -0,0 +1,331 @@
+use serde::Serialize;
+use primitive_types::U512;
+use zkwasm_rest_abi::StorageData;
+use crate::config::PRICE_PRECISION;
+use crate::error::*;
+use crate::math_safe::*;
+
+// 定价模型编号（CREATE_MARKET 参数与存储中使用）
+pub const PRICING_CPMM: u64 = 0;
+pub const PRICING_LMSR: u64 = 1;
+
+/// 定价引擎。state 为市场每个结果的定价状态：
+/// CPMM 为虚拟流动性，LMSR 为做市商已售出的份额 q_i。
+pub trait PricingEngine {
+    /// 验证初始定价状态
+    fn validate_state(&self, state: &[u64]) -> Result<(), u32>;
+
+    /// 结果 index 的当前价格，所有结果价格之和为 PRICE_PRECISION（允许取整误差）
+    fn price(&self, state: &[u64], index: usize) -> Result<u64, u32>;
+
+    /// 以净金额买入结果 index，返回新状态与获得的份额
+    fn buy(&self, state: &[u64], index: usize, net_amount: u64) -> Result<(Vec<u64>, u64), u32>;
+
+    /// 卖出结果 index 的份额，返回新状态与卖出总额（未扣费）
+    fn sell(&self, state: &[u64], index: usize, shares: u64) -> Result<(Vec<u64>, u64), u32>;
+
+    /// 买入恰好 shares 份结果 index，返回新状态与所需净金额（向上取整）
+    fn cost(&self, state: &[u64], index: usize, shares: u64) -> Result<(Vec<u64>, u64), u32>;
+
+    /// 以抵押品计的市场深度，流动性提供者按此比例缩放定价状态
+    fn depth(&self, state: &[u64]) -> u64;
+
+    /// 花费 amount 买入时，平均价格不高于 limit_price（PRICE_PRECISION = 1.0）至少应得的份额
+    fn min_shares_at_price(&self, amount: u64, limit_price: u64) -> Result<u64, u32>;
+}
+
+/// 常量乘积做市商：Π r_i = k
+pub struct Cpmm;
+
+impl PricingEngine for Cpmm {
+    fn validate_state(&self, state: &[u64]) -> Result<(), u32> {
+        for &liquidity in state.iter() {
+            validate_liquidity(liquidity)?;
+        }
+        Ok(())
+    }
+
+    fn price(&self, state: &[u64], index: usize) -> Result<u64, u32> {
+        calculate_outcome_price_safe(state, index)
+    }
+
+    // 最大储备，与 FPMM 注资时最大储备增加注资额一致
+    fn depth(&self, state: &[u64]) -> u64 {
+        state.iter().copied().max().unwrap_or(0)
+    }
+
+    // 价格 p 时每份边际成本为 p / (1 - p)，平均价格不高于 L 即 shares >= amount * (1 - L) / L
+    fn min_shares_at_price(&self, amount: u64, limit_price: u64) -> Result<u64, u32> {
+        safe_div_high_precision(amount, safe_sub(PRICE_PRECISION, limit_price)?, limit_price)
+    }
+
+    // 其他结果流动性增加净金额，买入结果按常量乘积减少
+    fn buy(&self, state: &[u64], index: usize, net_amount: u64) -> Result<(Vec<u64>, u64), u32> {
+        let k = calculate_k_multi_safe(state)?;
+        let mut new_liquidity = state.to_vec();
+        let mut others = Vec::with_capacity(new_liquidity.len() - 1);
+        for (i, liquidity) in new_liquidity.iter_mut().enumerate() {
+            if i != index {
+                *liquidity = safe_add(*liquidity, net_amount)?;
+                others.push(*liquidity);
+            }
+        }
+        new_liquidity[index] = calculate_new_liquidity_multi_safe(k, &others)?;
+        let shares = state[index].saturating_sub(new_liquidity[index]);
+        Ok((new_liquidity, shares))
+    }
+
+    // 卖出结果流动性增加份额数，其他结果等量减少
+    fn sell(&self, state: &[u64], index: usize, shares: u64) -> Result<(Vec<u64>, u64), u32> {
+        let k = calculate_k_multi_safe(state)?;
+        let new_index_liquidity = safe_add(state[index], shares)?;
+        let target = k / U512::from(new_index_liquidity);
+
+        let others: Vec<u64> = state.iter().enumerate()
+            .filter(|(i, _)| *i != index)
+            .map(|(_, liquidity)| *liquidity)
+            .collect();
+        let gross_amount = calculate_uniform_withdrawal_safe(target, &others)?;
+
+        let mut new_liquidity = state.to_vec();
+        for (i, liquidity) in new_liquidity.iter_mut().enumerate() {
+            if i == index {
+                *liquidity = new_index_liquidity;
+            } else {
+                *liquidity = safe_sub(*liquidity, gross_amount)?;
+                if *liquidity < MIN_LIQUIDITY {
+                    return Err(ERROR_INVALID_CALCULATION);
+                }
+            }
+        }
+        Ok((new_liquidity, gross_amount))
+    }
+
+    // 买入结果流动性减少份额数，其他结果等量增加直至乘积不小于 k
+    fn cost(&self, state: &[u64], index: usize, shares: u64) -> Result<(Vec<u64>, u64), u32> {
+        let new_index_liquidity = safe_sub(state[index], shares)?;
+        if new_index_liquidity < MIN_LIQUIDITY {
+            return Err(ERROR_INVALID_CALCULATION);
+        }
+
+        let k = calculate_k_multi_safe(state)?;
+        let divisor = U512::from(new_index_liquidity);
+        let target = (k + divisor - 1) / divisor;
+
+        let others: Vec<u64> = state.iter().enumerate()
+            .filter(|(i, _)| *i != index)
+            .map(|(_, liquidity)| *liquidity)
+            .collect();
+        let net_amount = calculate_uniform_deposit_safe(target, &others)?;
+
+        let mut new_liquidity = state.to_vec();
+        for (i, liquidity) in new_liquidity.iter_mut().enumerate() {
+            if i == index {
+                *liquidity = new_index_liquidity;
+            } else {
+                *liquidity = safe_add(*liquidity, net_amount)?;
+            }
+        }
+        Ok((new_liquidity, net_amount))
+    }
+}
+
+/// 对数市场评分规则：C(q) = b * ln(Σ e^(q_i / b))，做市商最大亏损为 b * ln(n)
+pub struct Lmsr {
+    pub b: u64,
+}
+
+impl Lmsr {
+    // 定点数权重 e^((q_j - max_q) / b)，以最大份额为基准防止溢出
+    fn weights(&self, state: &[u64]) -> Result<(Vec<u128>, u128), u32> {
+        let max_q = *state.iter().max().ok_or(ERROR_INVALID_CALCULATION)?;
+        let mut weights = Vec::with_capacity(state.len());
+        let mut total: u128 = 0;
+        for &q in state.iter() {
+            let exponent = -((max_q - q) as i128 * FIXED_ONE as i128 / self.b as i128);
+            let weight = exp_fixed_safe(exponent)?;
+            total = total.checked_add(weight).ok_or(ERROR_OVERFLOW)?;
+            weights.push(weight);
+        }
+        Ok((weights, total))
+    }
+
+    // b * x / FIXED_ONE 向下取整
+    fn scale_down(&self, x: i128) -> i128 {
+        (self.b as i128 * x).div_euclid(FIXED_ONE as i128)
+    }
+
+    // b * x / FIXED_ONE 向上取整
+    fn scale_up(&self, x: i128) -> i128 {
+        -(-(self.b as i128 * x)).div_euclid(FIXED_ONE as i128)
+    }
+}
+
+impl PricingEngine for Lmsr {
+    fn validate_state(&self, state: &[u64]) -> Result<(), u32> {
+        validate_liquidity(self.b)?;
+        for &q in state.iter() {
+            if q > MAX_SHARES {
+                return Err(ERROR_BET_TOO_LARGE);
+            }
+        }
+        Ok(())
+    }
+
+    fn depth(&self, _state: &[u64]) -> u64 {
+        self.b
+    }
+
+    // 价格即每份边际成本，平均价格不高于 L 即 shares >= amount / L
+    fn min_shares_at_price(&self, amount: u64, limit_price: u64) -> Result<u64, u32> {
+        safe_div_high_precision(amount, PRICE_PRECISION, limit_price)
+    }
+
+    // p_i = e^(q_i / b) / Σ e^(q_j / b)
+    fn price(&self, state: &[u64], index: usize) -> Result<u64, u32> {
+        if index >= state.len() {
+            return Err(ERROR_INVALID_BET_TYPE);
+        }
+        let (weights, total) = self.weights(state)?;
+        let price = weights[index] * PRICE_PRECISION as u128 / total;
+        Ok(price as u64)
+    }
+
+    // 成本差 C(q + s) - C(q) = x 的解析解：
+    // e^(q_i' / b) = e^(x / b) * S - (S - e^(q_i / b))，s = q_i' - q_i
+    // 提出 e^(x / b) 在对数空间计算，x 远大于 b 时也不会溢出
+    fn buy(&self, state: &[u64], index: usize, net_amount: u64) -> Result<(Vec<u64>, u64), u32> {
+        let (weights, total) = self.weights(state)?;
+        let max_q = *state.iter().max().ok_or(ERROR_INVALID_CALCULATION)?;
+
+        let exponent = net_amount as i128 * FIXED_ONE as i128 / self.b as i128;
+        let decay = exp_fixed_safe(-exponent)?;
+        let remaining = total.saturating_sub((total - weights[index]) * decay / FIXED_ONE);
+        if remaining == 0 {
+            return Ok((state.to_vec(), 0));
+        }
+
+        // q_i' = max_q + b * (x / b + ln(S - (S - e_i) * e^(-x / b)))，份额向下取整
+        let new_q = max_q as i128 + self.scale_down(exponent + ln_fixed_safe(remaining)?);
+        let shares = (new_q - state[index] as i128).max(0);
+        if shares > u64::MAX as i128 {
+            return Err(ERROR_OVERFLOW);
+        }
+
+        let shares = shares as u64;
+        let mut new_state = state.to_vec();
+        new_state[index] = safe_add(state[index], shares)?;
+        Ok((new_state, shares))
+    }
+
+    // 卖出总额 C(q) - C(q - s) = b * ln(S / (S - e_i + e_i * e^(-s / b)))
+    fn sell(&self, state: &[u64], index: usize, shares: u64) -> Result<(Vec<u64>, u64), u32> {
+        let (weights, total) = self.weights(state)?;
+
+        let decay = exp_fixed_safe(-(shares as i128 * FIXED_ONE as i128 / self.b as i128))?;
+        let remaining = total - weights[index] + weights[index] * decay / FIXED_ONE;
+        if remaining == 0 {
+            return Err(ERROR_INVALID_CALCULATION);
+        }
+
+        let log_ratio = ln_fixed_safe(total)? - ln_fixed_safe(remaining)?;
+        let gross_amount = self.scale_down(log_ratio).max(0);
+        if gross_amount > u64::MAX as i128 {
+            return Err(ERROR_OVERFLOW);
+        }
+
+        // 价格只取决于 q 的差值：q_i 不足时改为其他结果等量增加
+        let mut new_state = state.to_vec();
+        if state[index] >= shares {
+            new_state[index] = state[index] - shares;
+        } else {
+            let shift = shares - state[index];
+            for (i, q) in new_state.iter_mut().enumerate() {
+                *q = if i == index { 0 } else { safe_add(*q, shift)? };
+            }
+        }
+        Ok((new_state, gross_amount as u64))
+    }
+
+    // 买入成本 C(q + s) - C(q) = b * ln((S - e_i + e_i * e^(s / b)) / S)
+    // e_i * e^(s / b) = e^a，a = (s - (max_q - q_i)) / b；a 过大时提出 e^a 在对数空间计算
+    fn cost(&self, state: &[u64], index: usize, shares: u64) -> Result<(Vec<u64>, u64), u32> {
+        let (weights, total) = self.weights(state)?;
+        let max_q = *state.iter().max().ok_or(ERROR_INVALID_CALCULATION)?;
+
+        let exponent = (shares as i128 - (max_q - state[index]) as i128) * FIXED_ONE as i128 / self.b as i128;
+        let rest = total - weights[index];
+        let log_new_total = if exponent <= MAX_EXP_INPUT {
+            ln_fixed_safe(rest + exp_fixed_safe(exponent)?)?
+        } else {
+            exponent + ln_fixed_safe(FIXED_ONE + rest * exp_fixed_safe(-exponent)? / FIXED_ONE)?
+        };
+
+        let log_ratio = log_new_total - ln_fixed_safe(total)?;
+        let net_amount = self.scale_up(log_ratio).max(0);
+        if net_amount > u64::MAX as i128 {
+            return Err(ERROR_OVERFLOW);
+        }
+
+        let mut new_state = state.to_vec();
+        new_state[index] = safe_add(state[index], shares)?;
+        Ok((new_state, net_amount as u64))
+    }
+}
+
+/// 市场使用的定价模型
+#[derive(Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum PricingModel {
+    #[default]
+    Cpmm,
+    Lmsr { b: u64 },
+}
+
+impl PricingModel {
+    pub fn from_params(kind: u64, b: u64) -> Option<Self> {
+        match kind {
+            PRICING_CPMM => Some(PricingModel::Cpmm),
+            PRICING_LMSR => Some(PricingModel::Lmsr { b }),
+            _ => None,
+        }
+    }
+
+    // 深度按 numerator / denominator 缩放后的模型（LMSR 缩放 b）
+    pub fn scaled(&self, numerator: u64, denominator: u64) -> Result<Self, u32> {
+        match *self {
+            PricingModel::Cpmm => Ok(PricingModel::Cpmm),
+            PricingModel::Lmsr { b } => Ok(PricingModel::Lmsr {
+                b: safe_div_high_precision(b, numerator, denominator)?,
+            }),
+        }
+    }
+
+    pub fn engine(&self) -> Box<dyn PricingEngine> {
+        match *self {
+            PricingModel::Cpmm => Box::new(Cpmm),
+            PricingModel::Lmsr { b } => Box::new(Lmsr { b }),
+        }
+    }
+}
+
+impl StorageData for PricingModel {
+    fn from_data(u64data: &mut std::slice::IterMut<u64>) -> Self {
+        let kind = *u64data.next().unwrap();
+        let b = *u64data.next().unwrap();
+        PricingModel::from_params(kind, b).unwrap()
+    }
+
+    fn to_data(&self, data: &mut Vec<u64>) {
+        match *self {
+            PricingModel::Cpmm => {
+                data.push(PRICING_CPMM);
+                data.push(0);
+            }
+            PricingModel::Lmsr { b } => {
+                data.push(PRICING_LMSR);
+                data.push(b);
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use serde::Serialize;
+use zkwasm_rest_abi::{StorageData, MERKLE_MAP};
+use crate::config::{BOND_TREASURY_SHARE_BPS, FEE_BASIS_POINTS};
+use crate::error::*;
+use crate::event::{insert_event, EVENT_PROPOSAL_UPDATE};
+use crate::market::Market;
+use crate::math_safe::{safe_add, safe_mul, safe_sub};
+
+// Proposal status reported in PROPOSAL_UPDATE events
+pub const PROPOSAL_PROPOSED: u64 = 0;
+pub const PROPOSAL_CHALLENGED: u64 = 1;
+pub const PROPOSAL_SETTLED: u64 = 2;
+
+// Bond paid out to each player when a proposal is settled
+pub type BondPayouts = Vec<([u64; 2], u64)>;
+
+/// Optimistic resolution: a player bonds an outcome, which finalizes after the
+/// dispute window unless another player matches the bond. A challenge escalates
+/// to the admin, and the side that loses forfeits its bond once the market is final.
+#[derive(Serialize, Clone, Debug)]
+pub struct ResolutionProposal {
+    pub market_id: u64,
+    pub proposer: [u64; 2],
+    pub outcome: u64, // settled outcome, as recorded by MarketData::resolved_outcome
+    pub bond: u64,
+    pub challenger: Option<[u64; 2]>, // posted the same bond against the outcome
+}
+
+impl ResolutionProposal {
+    pub fn get(market_id: u64) -> Option<Self> {
+        let kvpair = unsafe { &mut MERKLE_MAP };
+        let mut data = kvpair.get(&Market::to_proposal_key(market_id));
+        if data.is_empty() {
+            return None;
+        }
+        let mut proposal = Self::from_data(&mut data.iter_mut());
+        proposal.market_id = market_id;
+        Some(proposal)
+    }
+
+    pub fn store(&self) {
+        let mut data = vec![];
+        self.to_data(&mut data);
+        let kvpair = unsafe { &mut MERKLE_MAP };
+        kvpair.set(&Market::to_proposal_key(self.market_id), data.as_slice());
+    }
+
+    // Drop the proposal once its bonds are paid out
+    pub fn remove(market_id: u64) {
+        let kvpair = unsafe { &mut MERKLE_MAP };
+        kvpair.set(&Market::to_proposal_key(market_id), &[]);
+    }
+
+    pub fn challenge(&mut self, pid: &[u64; 2]) -> Result<(), u32> {
+        if self.challenger.is_some() || self.proposer == *pid {
+            return Err(ERROR_UNAUTHORIZED);
+        }
+        self.challenger = Some(*pid);
+        Ok(())
+    }
+
+    /// Split the bonds once the market is final. Returns the payout to each
+    /// player and the treasury's cut of the losing bond.
+    pub fn settle(&self, final_outcome: u64) -> Result<(BondPayouts, u64), u32> {
+        let proposer_won = self.outcome == final_outcome;
+        let treasury_cut = safe_mul(self.bond, BOND_TREASURY_SHARE_BPS)? / FEE_BASIS_POINTS;
+        match self.challenger {
+            None if proposer_won => Ok((vec![(self.proposer, self.bond)], 0)),
+            // A wrong outcome nobody challenged was corrected by the admin
+            None => Ok((vec![], self.bond)),
+            Some(challenger) => {
+                let winner = if proposer_won { self.proposer } else { challenger };
+                let reward = safe_sub(self.bond, treasury_cut)?;
+                Ok((vec![(winner, safe_add(self.bond, reward)?)], treasury_cut))
+            }
+        }
+    }
+}
+
+pub fn emit_proposal_event(proposal: &ResolutionProposal, status: u64, counter: u64) {
+    let challenger = proposal.challenger.unwrap_or([0, 0]);
+    let mut data = vec![
+        proposal.market_id,
+        proposal.proposer[0],
+        proposal.proposer[1],
+        challenger[0],
+        challenger[1],
+        proposal.outcome,
+        proposal.bond,
+        status,
+        counter,
+    ];
+    insert_event(EVENT_PROPOSAL_UPDATE, &mut data);
+}
+
+impl StorageData for ResolutionProposal {
+    fn from_data(u64data: &mut std::slice::IterMut<u64>) -> Self {
+        let proposer = [*u64data.next().unwrap(), *u64data.next().unwrap()];
+        let outcome = *u64data.next().unwrap();
+        let bond = *u64data.next().unwrap();
+        // 0 = unchallenged, 1 followed by the challenger's pid otherwise
+        let challenger = match *u64data.next().unwrap() {
+            0 => None,
+            _ => Some([*u64data.next().unwrap(), *u64data.next().unwrap()]),
+        };
+        ResolutionProposal {
+            // Filled in by ResolutionProposal::get
+            market_id: 0,
+            proposer,
+            outcome,
+            bond,
+            challenger,
+        }
+    }
+
+    fn to_data(&self, data: &mut Vec<u64>) {
+        data.push(self.proposer[0]);
+        data.push(self.proposer[1]);
+        data.push(self.outcome);
+        data.push(self.bond);
+        match self.challenger {
+            None => data.push(0),
+            Some(pid) => data.extend_from_slice(&[1, pid[0], pid[1]]),
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use serde::Serialize;
+use zkwasm_rest_abi::{StorageData, MERKLE_MAP};
+use crate::error::*;
+use crate::event::{insert_event, EVENT_RESOLUTION_VOTE};
+use crate::market::Market;
+
+// Bounds the committee stored with each market
+pub const MAX_RESOLVERS: usize = 8;
+
+/// M-of-N committee that resolves a market in place of the admin.
+/// Each member votes with their own signed transaction; the outcome is proposed
+/// once `threshold` members agree, and the votes are cleared for the next round.
+#[derive(Serialize, Clone, Debug)]
+pub struct ResolverCommittee {
+    pub market_id: u64,
+    pub members: Vec<[u64; 2]>, // player ids of the members' pubkeys
+    pub threshold: u64,
+    pub votes: Vec<Option<u64>>, // current vote of each member, indexed like members
+}
+
+impl ResolverCommittee {
+    pub fn new(market_id: u64, members: Vec<[u64; 2]>, threshold: u64) -> Result<Self, u32> {
+        if members.is_empty() || members.len() > MAX_RESOLVERS {
+            return Err(ERROR_INVALID_COMMITTEE);
+        }
+        if threshold == 0 || threshold > members.len() as u64 {
+            return Err(ERROR_INVALID_COMMITTEE);
+        }
+        for (i, member) in members.iter().enumerate() {
+            if members[..i].contains(member) {
+                return Err(ERROR_INVALID_COMMITTEE);
+            }
+        }
+        let votes = vec![None; members.len()];
+        Ok(ResolverCommittee { market_id, members, threshold, votes })
+    }
+
+    pub fn get(market_id: u64) -> Option<Self> {
+        let kvpair = unsafe { &mut MERKLE_MAP };
+        let mut data = kvpair.get(&Market::to_resolvers_key(market_id));
+        if data.is_empty() {
+            return None;
+        }
+        let mut u64data = data.iter_mut();
+        let mut committee = Self::from_data(&mut u64data);
+        committee.market_id = market_id;
+        Some(committee)
+    }
+
+    pub fn store(&self) {
+        let mut data = vec![];
+        self.to_data(&mut data);
+        let kvpair = unsafe { &mut MERKLE_MAP };
+        kvpair.set(&Market::to_resolvers_key(self.market_id), data.as_slice());
+    }
+
+    // Record a member's vote, replacing any earlier one, and return how many
+    // members now back that outcome
+    pub fn vote(&mut self, pid: &[u64; 2], outcome: u64) -> Result<u64, u32> {
+        let index = self.members.iter()
+            .position(|member| member == pid)
+            .ok_or(ERROR_UNAUTHORIZED)?;
+        self.votes[index] = Some(outcome);
+        Ok(self.votes.iter().filter(|vote| **vote == Some(outcome)).count() as u64)
+    }
+
+    pub fn clear_votes(&mut self) {
+        self.votes.iter_mut().for_each(|vote| *vote = None);
+    }
+}
+
+pub fn emit_vote_event(committee: &ResolverCommittee, pid: &[u64; 2], outcome: u64, votes: u64, counter: u64) {
+    let mut data = vec![
+        committee.market_id,
+        pid[0],
+        pid[1],
+        outcome,
+        votes,
+        committee.threshold,
+        counter,
+    ];
+    insert_event(EVENT_RESOLUTION_VOTE, &mut data);
+}
+
+impl StorageData for ResolverCommittee {
+    fn from_data(u64data: &mut std::slice::IterMut<u64>) -> Self {
+        let threshold = *u64data.next().unwrap();
+        let count = *u64data.next().unwrap();
+        let mut members = Vec::with_capacity(count as usize);
+        let mut votes = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            members.push([*u64data.next().unwrap(), *u64data.next().unwrap()]);
+            // 0 = no vote, 1 followed by the outcome otherwise
+            let voted = *u64data.next().unwrap() != 0;
+            let outcome = *u64data.next().unwrap();
+            votes.push(if voted { Some(outcome) } else { None });
+        }
+        ResolverCommittee {
+            // Filled in by ResolverCommittee::get
+            market_id: 0,
+            members,
+            threshold,
+            votes,
+        }
+    }
+
+    fn to_data(&self, data: &mut Vec<u64>) {
+        data.push(self.threshold);
+        data.push(self.members.len() as u64);
+        for (member, vote) in self.members.iter().zip(self.votes.iter()) {
+            data.push(member[0]);
+            data.push(member[1]);
+            data.push(if vote.is_some() { 1 } else { 0 });
+            data.push(vote.unwrap_or(0));
+        }
+    }
+}
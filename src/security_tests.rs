@@ -283,9 +283,12 @@ mod security_tests {
 
 #[cfg(test)]
 mod market_safe_tests {
-    use crate::market::MarketData;
+    use zkwasm_rest_abi::StorageData;
+    use crate::market::{MarketData, MarketStatus, MarketText, Pause, ScalarRange, MAX_MARKET_TEXT_BYTES, OUTCOME_INVALID, OUTCOME_LONG, OUTCOME_SHORT, OUTCOME_YES};
+    use crate::config::{PLATFORM_FEE_RATE, PRICE_PRECISION};
     use crate::error::*;
-    use crate::math_safe::{MAX_BET_AMOUNT, MAX_SHARES};
+    use crate::math_safe::*;
+    use crate::pricing::PricingModel;
 
     #[test]
     fn test_safe_market_creation() {
@@ -299,6 +302,673 @@ mod market_safe_tests {
         assert!(market.is_ok());
     }
 
+    pub(super) fn outcome_text(outcome_count: usize) -> MarketText {
+        MarketText {
+            outcomes: (0..outcome_count).map(|i| format!("Outcome {}", i)).collect(),
+            ..MarketText::default()
+        }
+    }
+
+    #[test]
+    fn test_market_creation_validation() {
+        // 时间顺序错误
+        let market = MarketData::with_liquidity(outcome_text(2), 100, 100, 200, vec![1_000_000, 1_000_000]);
+        assert_eq!(market.err(), Some(ERROR_INVALID_MARKET_TIME));
+        let market = MarketData::with_liquidity(outcome_text(2), 0, 200, 100, vec![1_000_000, 1_000_000]);
+        assert_eq!(market.err(), Some(ERROR_INVALID_MARKET_TIME));
+
+        // 初始流动性越界
+        let market = MarketData::with_liquidity(outcome_text(2), 0, 100, 100, vec![MIN_LIQUIDITY - 1, 1_000_000]);
+        assert_eq!(market.err(), Some(ERROR_INVALID_CALCULATION));
+        let market = MarketData::with_liquidity(outcome_text(2), 0, 100, 100, vec![1_000_000, MAX_LIQUIDITY + 1]);
+        assert_eq!(market.err(), Some(ERROR_LIQUIDITY_TOO_HIGH));
+
+        // 结果数量越界或与结果名称数量不一致
+        let market = MarketData::with_liquidity(outcome_text(1), 0, 100, 100, vec![1_000_000]);
+        assert_eq!(market.err(), Some(ERROR_INVALID_OUTCOME));
+        let market = MarketData::with_liquidity(outcome_text(MAX_OUTCOMES + 1), 0, 100, 100, vec![1_000_000; MAX_OUTCOMES + 1]);
+        assert_eq!(market.err(), Some(ERROR_INVALID_OUTCOME));
+        let market = MarketData::with_liquidity(outcome_text(3), 0, 100, 100, vec![1_000_000, 1_000_000]);
+        assert_eq!(market.err(), Some(ERROR_INVALID_OUTCOME));
+
+        // 非对称初始流动性决定初始价格（[NO, YES]）
+        let market = MarketData::with_liquidity(outcome_text(2), 0, 100, 100, vec![1_000_000, 3_000_000]).unwrap();
+        assert_eq!(market.get_yes_price().unwrap(), 250_000);
+    }
+
+    #[test]
+    fn test_binary_market_matches_two_sided_amm() {
+        let mut market = MarketData::new(
+            "Test Market".to_string(),
+            "Test Description".to_string(),
+            0,
+            1000,
+            1000
+        ).unwrap();
+
+        // 与原有二元常量乘积公式逐位一致
+        let net_amount = calculate_net_amount_safe(100_000).unwrap();
+        let k = calculate_k_safe(1_000_000, 1_000_000).unwrap();
+        let new_yes = calculate_new_liquidity_safe(k, 1_000_000 + net_amount).unwrap();
+        let shares = market.place_bet(OUTCOME_YES, 100_000, PLATFORM_FEE_RATE).unwrap();
+        assert_eq!(shares, 1_000_000 - new_yes);
+        assert_eq!(market.liquidity, vec![1_000_000 + net_amount, new_yes]);
+
+        let k = calculate_k_safe(new_yes, 1_000_000 + net_amount).unwrap();
+        let new_no = calculate_new_liquidity_safe(k, new_yes + 1000).unwrap();
+        let (payout, fee) = market.calculate_sell_details(OUTCOME_YES, 1000, PLATFORM_FEE_RATE).unwrap();
+        assert_eq!(payout + fee, 1_000_000 + net_amount - new_no);
+    }
+
+    #[test]
+    fn test_lmsr_market_trading() {
+        let b = 1_000_000;
+        let lmsr = PricingModel::Lmsr { b };
+
+        // b 越界或初始份额过大
+        let market = MarketData::with_pricing(outcome_text(3), 0, 1000, 1000, PricingModel::Lmsr { b: 0 }, vec![0; 3]);
+        assert_eq!(market.err(), Some(ERROR_INVALID_CALCULATION));
+        let market = MarketData::with_pricing(outcome_text(3), 0, 1000, 1000, lmsr, vec![0, MAX_SHARES + 1, 0]);
+        assert_eq!(market.err(), Some(ERROR_BET_TOO_LARGE));
+
+        // 初始份额全为 0 时价格均等
+        let mut market = MarketData::with_pricing(outcome_text(3), 0, 1000, 1000, lmsr, vec![0; 3]).unwrap();
+        assert_eq!(market.get_price(0).unwrap(), 333_333);
+
+        // 买入推高价格，价格之和保持为 1
+        let shares = market.place_bet(1, 100_000, PLATFORM_FEE_RATE).unwrap();
+        assert!(shares > 100_000);
+        assert_eq!(market.liquidity, vec![0, shares, 0]);
+        let prices: Vec<u64> = (0..3).map(|i| market.get_price(i).unwrap()).collect();
+        assert!(prices[1] > 333_333 && prices[0] == prices[2]);
+        assert!(PRICE_PRECISION - prices.iter().sum::<u64>() < 3);
+
+        // 立即卖回不会多于买入时的净金额
+        let (payout, fee) = market.calculate_sell_details(1, shares, PLATFORM_FEE_RATE).unwrap();
+        assert!(payout + fee <= calculate_net_amount_safe(100_000).unwrap());
+        assert!(payout + fee + 10 >= calculate_net_amount_safe(100_000).unwrap());
+
+        // 存储往返保留定价模型
+        let mut data = vec![];
+        market.to_data(&mut data);
+        let restored = MarketData::from_data(&mut data.iter_mut());
+        assert_eq!(restored.pricing, lmsr);
+        assert_eq!(restored.liquidity, market.liquidity);
+    }
+
+    #[test]
+    fn test_buy_exact_shares() {
+        let cpmm = MarketData::with_liquidity(outcome_text(3), 0, 1000, 1000, vec![1_000_000; 3]).unwrap();
+        let lmsr = MarketData::with_pricing(outcome_text(3), 0, 1000, 1000, PricingModel::Lmsr { b: 1_000_000 }, vec![0; 3]).unwrap();
+
+        for mut market in [cpmm, lmsr] {
+            // 成本按不利于交易者的方向取整：花同样的钱下注至少得到同样的份额
+            let (cost, fee) = market.calculate_cost(2, 50_000, PLATFORM_FEE_RATE).unwrap();
+            assert_eq!(fee, calculate_fee_safe(cost).unwrap());
+            assert!(market.calculate_shares(2, cost, PLATFORM_FEE_RATE).unwrap() >= 50_000);
+            assert!(market.calculate_shares(2, cost - 10, PLATFORM_FEE_RATE).unwrap() < 50_000);
+
+            let prize_pool = market.prize_pool;
+            assert_eq!(market.buy_shares(2, 50_000, PLATFORM_FEE_RATE), Ok(cost));
+            assert_eq!(market.total_shares[2], 50_000);
+            assert_eq!(market.prize_pool, prize_pool + cost - fee);
+            assert_eq!(market.calculate_cost(3, 1000, PLATFORM_FEE_RATE), Err(ERROR_INVALID_BET_TYPE));
+        }
+    }
+
+    #[test]
+    fn test_slippage_limits() {
+        let mut market = MarketData::with_liquidity(outcome_text(2), 0, 1000, 1000, vec![1_000_000; 2]).unwrap();
+
+        // 投注：最少份额高于报价一份即拒绝，等于报价则成交
+        let shares = market.calculate_shares(OUTCOME_YES, 10_000, PLATFORM_FEE_RATE).unwrap();
+        assert_eq!(market.quote_bet(OUTCOME_YES, 10_000, PLATFORM_FEE_RATE, shares + 1), Err(ERROR_SLIPPAGE_EXCEEDED));
+        assert_eq!(market.quote_bet(OUTCOME_YES, 10_000, PLATFORM_FEE_RATE, shares), Ok(shares));
+
+        // 买入精确份额：最高成本低于报价一单位即拒绝
+        let (cost, fee) = market.calculate_cost(OUTCOME_YES, 5_000, PLATFORM_FEE_RATE).unwrap();
+        assert_eq!(market.quote_cost(OUTCOME_YES, 5_000, PLATFORM_FEE_RATE, cost - 1), Err(ERROR_SLIPPAGE_EXCEEDED));
+        assert_eq!(market.quote_cost(OUTCOME_YES, 5_000, PLATFORM_FEE_RATE, cost), Ok((cost, fee)));
+
+        // 卖出：最低收益高于报价一单位即拒绝
+        market.place_bet(OUTCOME_YES, 10_000, PLATFORM_FEE_RATE).unwrap();
+        let (payout, fee) = market.calculate_sell_details(OUTCOME_YES, shares, PLATFORM_FEE_RATE).unwrap();
+        assert_eq!(market.quote_sale(OUTCOME_YES, shares, PLATFORM_FEE_RATE, payout + 1), Err(ERROR_SLIPPAGE_EXCEEDED));
+        assert_eq!(market.quote_sale(OUTCOME_YES, shares, PLATFORM_FEE_RATE, payout), Ok((payout, fee)));
+        assert_eq!(market.sell_shares(OUTCOME_YES, shares, PLATFORM_FEE_RATE), Ok(payout));
+    }
+
+    #[test]
+    fn test_lmsr_worst_case_subsidy() {
+        let b = 1_000_000;
+        let mut market = MarketData::with_pricing(outcome_text(3), 0, 1000, 1000, PricingModel::Lmsr { b }, vec![0; 3]).unwrap();
+
+        // 持续买入同一结果，做市商亏损（份额 - 净收入）不超过 b * ln(3)
+        let mut total_shares = 0;
+        for _ in 0..50 {
+            total_shares += market.place_bet(0, 1_000_000, PLATFORM_FEE_RATE).unwrap();
+        }
+        let subsidy = total_shares - market.prize_pool;
+        assert!(subsidy <= b * 1_098_613 / 1_000_000);
+        assert!(market.get_price(0).unwrap() > 999_000);
+    }
+
+    #[test]
+    fn test_lmsr_trade_far_above_b() {
+        // 单笔交易超过 40 * b 时指数仍在范围内，按接近 1 的价格成交
+        let b = 10_000;
+        let mut market = MarketData::with_pricing(outcome_text(2), 0, 1000, 1000, PricingModel::Lmsr { b }, vec![0; 2]).unwrap();
+        let amount = 100 * b;
+        let net_amount = amount - calculate_fee_safe(amount).unwrap();
+        let shares = market.place_bet(0, amount, PLATFORM_FEE_RATE).unwrap();
+        assert!(shares > net_amount && shares <= net_amount + b * 693_148 / 1_000_000);
+        assert!(market.get_price(0).unwrap() > 999_000);
+
+        // 买入远超 b 的份额：价格接近 1 的结果成本接近份额数；另一结果只为超过 q_0 的部分付费
+        let (cost, fee) = market.calculate_cost(0, 50 * b, PLATFORM_FEE_RATE).unwrap();
+        assert!(cost - fee <= 50 * b && cost - fee > 50 * b - 10);
+        let (cost, fee) = market.calculate_cost(1, 150 * b, PLATFORM_FEE_RATE).unwrap();
+        assert!(cost - fee >= 150 * b - shares && cost - fee < 150 * b - shares + b);
+    }
+
+    #[test]
+    fn test_liquidity_provision() {
+        let cpmm = MarketData::with_liquidity(outcome_text(3), 0, 1000, 1000, vec![1_000_000; 3]).unwrap();
+        let lmsr = MarketData::with_pricing(outcome_text(3), 0, 1000, 1000, PricingModel::Lmsr { b: 1_000_000 }, vec![0; 3]).unwrap();
+
+        for mut market in [cpmm, lmsr] {
+            market.place_bet(2, 200_000, PLATFORM_FEE_RATE).unwrap();
+            let prices: Vec<u64> = (0..3).map(|i| market.get_price(i).unwrap()).collect();
+
+            // 注入流动性不改变价格，并降低同等下注的滑点；抵押品作为完整份额组进入奖池
+            let quote = market.calculate_shares(2, 50_000, PLATFORM_FEE_RATE).unwrap();
+            let prize_pool = market.prize_pool;
+            assert_eq!(market.add_liquidity(1_000_000), Ok(1_000_000));
+            assert_eq!(market.prize_pool, prize_pool + 1_000_000);
+            for (i, &price) in prices.iter().enumerate() {
+                assert!(market.get_price(i as u64).unwrap().abs_diff(price) <= 1);
+            }
+            assert!(market.calculate_shares(2, 50_000, PLATFORM_FEE_RATE).unwrap() > quote);
+
+            // 有 LP 后费用按比例分给 LP
+            let platform_fees = market.total_fees_collected + market.referrer_fees;
+            market.place_bet(0, 100_000, PLATFORM_FEE_RATE).unwrap();
+            let fee = calculate_fee_safe(100_000).unwrap();
+            assert_eq!(market.lp_fees, fee / 2);
+            assert_eq!(market.total_fees_collected + market.referrer_fees, platform_fees + fee - fee / 2);
+
+            // 后加入的 LP 按含费用的净值铸造份额
+            let lp_shares = market.add_liquidity(500_000).unwrap();
+            assert!(lp_shares < 500_000);
+
+            // 交易期内按面值赎回份额组并附带费用，超额赎回失败
+            assert_eq!(market.remove_liquidity(2_000_000, 0), Err(ERROR_INSUFFICIENT_BALANCE));
+            let payout = market.remove_liquidity(1_000_000, 0).unwrap();
+            assert!(payout > 1_000_000);
+            assert_eq!(market.total_lp_shares, lp_shares);
+
+            // 解决后按份额组的结算价值支付，不再触碰定价状态和奖池
+            market.resolve(2).unwrap();
+            let liquidity = market.liquidity.clone();
+            let prize_pool = market.prize_pool;
+            let expected = market.calculate_payout(&[market.lp_pool; 3]).unwrap() + market.lp_fees;
+            assert_eq!(market.remove_liquidity(lp_shares, 1000), Ok(expected));
+            assert_eq!((market.liquidity.clone(), market.prize_pool), (liquidity, prize_pool));
+            assert_eq!((market.lp_pool, market.lp_fees, market.total_lp_shares), (0, 0, 0));
+            assert_eq!(market.add_liquidity(1_000_000), Err(ERROR_MARKET_ALREADY_RESOLVED));
+        }
+    }
+
+    #[test]
+    fn test_liquidity_takes_resolution_risk() {
+        // 先到的 LP 赚取费用，之后才注入的 LP 立即赎回拿不到之前的费用
+        let mut market = MarketData::with_liquidity(outcome_text(2), 0, 1000, 1000, vec![1_000_000; 2]).unwrap();
+        let early = market.add_liquidity(1_000_000).unwrap();
+        market.place_bet(0, 200_000, PLATFORM_FEE_RATE).unwrap();
+        let lp_fees = market.lp_fees;
+        assert!(lp_fees > 0);
+
+        let late = market.add_liquidity(1_000_000).unwrap();
+        assert!(market.remove_liquidity(late, 0).unwrap() <= 1_000_000);
+        assert!(market.lp_pool + market.lp_fees >= 1_000_000 + lp_fees - 1);
+
+        // 交易结束到结果生效之间不能退出
+        assert_eq!(market.remove_liquidity(early, 1000), Err(ERROR_MARKET_NOT_RESOLVED));
+
+        // LP 的结算价值取决于结果：交易者押中时与其分享奖池，押错时 LP 独得整个奖池
+        let mut lost = market.clone();
+        lost.resolve(0).unwrap();
+        let mut won = market.clone();
+        won.resolve(1).unwrap();
+        let lost_payout = lost.remove_liquidity(early, 1000).unwrap();
+        let won_payout = won.remove_liquidity(early, 1000).unwrap();
+        assert_eq!(won_payout, won.prize_pool + market.lp_fees);
+        assert!(lost_payout < won_payout - 100_000);
+    }
+
+    #[test]
+    fn test_invalid_resolution_refunds() {
+        let mut market = MarketData::with_liquidity(outcome_text(3), 0, 1000, 1000, vec![1_000_000; 3]).unwrap();
+
+        // 两名交易者：A 买入 100_000，B 买入 50_000 后部分卖出
+        market.place_bet(0, 100_000, PLATFORM_FEE_RATE).unwrap();
+        market.add_cost_basis(100_000).unwrap();
+        let shares = market.place_bet(1, 50_000, PLATFORM_FEE_RATE).unwrap();
+        market.add_cost_basis(50_000).unwrap();
+        let payout = market.sell_shares(1, shares / 2, PLATFORM_FEE_RATE).unwrap();
+        market.remove_cost_basis(payout).unwrap();
+
+        // 未解决时没有退款
+        assert_eq!(market.calculate_refund(100_000), Ok(0));
+
+        // 扣除费用后奖池不足以全额退款，按净成本比例分配
+        market.resolve(OUTCOME_INVALID).unwrap();
+        assert!(market.invalid && market.outcome.is_none());
+        assert!(market.prize_pool < market.total_cost_basis);
+        let refund_a = market.calculate_refund(100_000).unwrap();
+        let refund_b = market.calculate_refund(50_000 - payout).unwrap();
+        assert!(refund_a < 100_000 && refund_b < 50_000 - payout);
+        assert!(market.prize_pool - (refund_a + refund_b) <= 1);
+        assert_eq!(market.calculate_payout(&[1000, 1000, 0]), Ok(0));
+
+        // 无效结果写入存储后保持不变
+        let mut data = vec![];
+        market.to_data(&mut data);
+        let restored = MarketData::from_data(&mut data.iter_mut());
+        assert!(restored.status == MarketStatus::Finalized && restored.invalid && restored.outcome.is_none());
+        assert_eq!(restored.total_cost_basis, market.total_cost_basis);
+
+        // 只能在开始前取消
+        let mut market = MarketData::with_liquidity(outcome_text(2), 100, 1000, 1000, vec![1_000_000; 2]).unwrap();
+        assert_eq!(market.cancel(100), Err(ERROR_MARKET_ALREADY_STARTED));
+        assert!(market.cancel(99).is_ok());
+        assert!(market.invalid && market.status == MarketStatus::Cancelled);
+        assert_eq!(market.cancel(99), Err(ERROR_MARKET_ALREADY_RESOLVED));
+    }
+
+    #[test]
+    fn test_dispute_window() {
+        use crate::config::DISPUTE_PERIOD_TICKS;
+        let mut market = MarketData::with_liquidity(outcome_text(3), 0, 1000, 1000, vec![1_000_000; 3]).unwrap();
+        assert_eq!(market.dispute(1000), Err(ERROR_DISPUTE_WINDOW_CLOSED));
+
+        // 提出的结果在争议期内可以更正，更正后重新计时
+        market.propose_resolution(0, 1000).unwrap();
+        assert!(market.status == MarketStatus::Proposed && !market.is_finalized(1000));
+        market.propose_resolution(2, 1100).unwrap();
+        assert_eq!(market.outcome, Some(2));
+        assert!(!market.is_finalized(1000 + DISPUTE_PERIOD_TICKS));
+        assert!(market.is_finalized(1100 + DISPUTE_PERIOD_TICKS));
+
+        // 被争议后争议期结束也不生效，直到管理员裁定
+        market.dispute(1200).unwrap();
+        assert_eq!(market.dispute(1200), Err(ERROR_DISPUTE_WINDOW_CLOSED));
+        assert!(!market.is_finalized(1100 + DISPUTE_PERIOD_TICKS));
+        market.propose_resolution(1, 1300).unwrap();
+        assert!(market.is_finalized(1300));
+        assert_eq!(market.propose_resolution(0, 1300), Err(ERROR_MARKET_ALREADY_RESOLVED));
+
+        let mut data = vec![];
+        market.to_data(&mut data);
+        let restored = MarketData::from_data(&mut data.iter_mut());
+        assert_eq!((restored.outcome, restored.dispute_end, restored.status), (Some(1), 1300, MarketStatus::Finalized));
+    }
+
+    #[test]
+    fn test_market_status_transitions() {
+        use crate::config::DISPUTE_PERIOD_TICKS;
+        let mut market = MarketData::with_liquidity(outcome_text(2), 100, 1000, 1200, vec![1_000_000; 2]).unwrap();
+        assert_eq!(market.status, MarketStatus::Scheduled);
+
+        // 时间驱动的状态推进：Scheduled -> Open -> Closed
+        assert_eq!(market.status_at(99), MarketStatus::Scheduled);
+        assert!(market.advance(100));
+        assert!(!market.advance(999));
+        assert!(market.is_active(999) && !market.is_active(1000));
+        assert!(market.advance(1000));
+        assert_eq!(market.status, MarketStatus::Closed);
+
+        // 交易结束后不能取消；到解决时间前不能提出结果，委员会也不能投票
+        assert_eq!(market.cancel(1000), Err(ERROR_MARKET_ALREADY_STARTED));
+        assert!(!market.can_resolve(1199) && market.can_resolve(1200));
+        assert_eq!(market.propose_resolution(0, 1199), Err(ERROR_MARKET_NOT_ENDED));
+        market.propose_resolution(0, 1200).unwrap();
+        assert_eq!(market.status, MarketStatus::Proposed);
+
+        // 争议期结束自动生效，写入存储后保持
+        let finalized = 1200 + DISPUTE_PERIOD_TICKS;
+        assert_eq!(market.status_at(finalized - 1), MarketStatus::Proposed);
+        assert!(market.advance(finalized));
+        assert_eq!(market.dispute(finalized), Err(ERROR_DISPUTE_WINDOW_CLOSED));
+        let mut data = vec![];
+        market.to_data(&mut data);
+        assert_eq!(MarketData::from_data(&mut data.iter_mut()).status, MarketStatus::Finalized);
+
+        // 开始前的市场不能提出结果
+        let mut scheduled = MarketData::with_liquidity(outcome_text(2), 100, 1000, 1000, vec![1_000_000; 2]).unwrap();
+        assert_eq!(scheduled.propose_resolution(0, 50), Err(ERROR_MARKET_NOT_ENDED));
+    }
+
+    #[test]
+    fn test_market_status_steps() {
+        let mut market = MarketData::with_liquidity(outcome_text(2), 100, 1000, 1200, vec![1_000_000; 2]).unwrap();
+
+        // 跳过整个交易期时逐步推进：Scheduled -> Open -> Closed，每一步单独发出事件
+        let mut steps = vec![];
+        while market.step(1000) {
+            steps.push(market.status);
+        }
+        assert_eq!(steps, vec![MarketStatus::Open, MarketStatus::Closed]);
+        assert!(!market.is_settled());
+
+        market.propose_resolution(1, 1200).unwrap();
+        assert!(market.step(1200 + crate::config::DISPUTE_PERIOD_TICKS));
+        assert_eq!(market.status, MarketStatus::Finalized);
+        assert!(market.is_settled());
+    }
+
+    #[test]
+    fn test_pause() {
+        use crate::state::GlobalState;
+        let mut state = GlobalState::new();
+        let mut market = MarketData::with_liquidity(outcome_text(2), 0, 1000, 1000, vec![1_000_000; 2]).unwrap();
+        assert_eq!(state.ensure_active(&market), Ok(0));
+
+        // 单个市场暂停交易，领取仍然开放
+        market.pause = Pause { trading: true, claims: false };
+        assert_eq!(state.ensure_active(&market), Err(ERROR_MARKET_PAUSED));
+        assert_eq!(state.ensure_claimable(&market), Ok(()));
+
+        // 暂停状态写入存储后保持不变
+        let mut data = vec![];
+        market.to_data(&mut data);
+        assert_eq!(MarketData::from_data(&mut data.iter_mut()).pause, market.pause);
+
+        // 全局暂停对所有市场生效，并可同时停止领取
+        market.pause = Pause::default();
+        state.pause = Pause { trading: true, claims: true };
+        assert_eq!(state.ensure_active(&market), Err(ERROR_MARKET_PAUSED));
+        assert_eq!(state.ensure_claimable(&market), Err(ERROR_MARKET_PAUSED));
+        let mut data = vec![];
+        state.to_data(&mut data);
+        assert_eq!(GlobalState::from_data(&mut data.iter_mut()).pause, state.pause);
+    }
+
+    #[test]
+    fn test_complete_sets() {
+        let mut market = MarketData::with_liquidity(outcome_text(3), 0, 1000, 1000, vec![1_000_000; 3]).unwrap();
+        let bet_shares = market.place_bet(OUTCOME_YES, 100_000, PLATFORM_FEE_RATE).unwrap();
+        let prices: Vec<u64> = (0..3).map(|i| market.get_price(i).unwrap()).collect();
+        let (pool, fees) = (market.prize_pool, market.total_fees_collected);
+
+        // 铸造不改变价格，不收费，抵押品全部进入奖池
+        market.mint_set(50_000).unwrap();
+        assert_eq!((0..3).map(|i| market.get_price(i).unwrap()).collect::<Vec<_>>(), prices);
+        assert_eq!(market.total_shares, vec![50_000, bet_shares + 50_000, 50_000]);
+        assert_eq!((market.prize_pool, market.total_fees_collected), (pool + 50_000, fees));
+
+        // 赎回受每个结果的总份额限制
+        assert_eq!(market.redeem_set(50_001), Err(ERROR_INSUFFICIENT_BALANCE));
+        assert_eq!(market.redeem_set(0), Err(ERROR_INVALID_BET_AMOUNT));
+        market.redeem_set(50_000).unwrap();
+        assert_eq!(market.total_shares, vec![0, bet_shares, 0]);
+        assert_eq!(market.prize_pool, pool);
+    }
+
+    #[test]
+    fn test_open_interest_cap() {
+        use crate::config::MAX_OPEN_INTEREST;
+        let mut market = MarketData::with_liquidity(outcome_text(3), 0, 1000, 1000, vec![1_000_000; 3]).unwrap();
+        let shares = market.place_bet(OUTCOME_YES, 100_000, PLATFORM_FEE_RATE).unwrap();
+        assert_eq!(market.open_interest(), Ok(shares));
+        assert!(market.check_open_interest().is_ok());
+
+        // LP 注入的份额组只增加深度，不计入未平仓份额
+        market.add_liquidity(1_000_000).unwrap();
+        assert_eq!(market.open_interest(), Ok(shares));
+
+        // 所有结果的份额合计计入上限
+        let lp_sets = market.lp_pool;
+        market.total_shares = vec![MAX_OPEN_INTEREST / 2 + lp_sets, MAX_OPEN_INTEREST / 2 + lp_sets, lp_sets];
+        assert!(market.check_open_interest().is_ok());
+        market.total_shares[2] += 1;
+        assert_eq!(market.check_open_interest(), Err(ERROR_OPEN_INTEREST_CAP_EXCEEDED));
+    }
+
+    #[test]
+    fn test_fee_schedule() {
+        use crate::config::MAX_FEE_BPS;
+        use crate::fee::FeeSchedule;
+
+        // 价格离 50% 越远费率越高，结束前线性加价
+        let schedule = FeeSchedule::new(100, 300, 1000, 200).unwrap();
+        assert_eq!(schedule.rate_bps(500_000, 5000), 100);
+        assert_eq!(schedule.rate_bps(250_000, 5000), 200);
+        assert_eq!(schedule.rate_bps(1_000_000, 5000), 300);
+        assert_eq!(schedule.rate_bps(500_000, 500), 200);
+        assert_eq!(schedule.rate_bps(0, 0), 500);
+
+        // 费率上限和无效的加价参数
+        assert!(FeeSchedule::new(MAX_FEE_BPS, 0, 0, 0).is_ok());
+        assert_eq!(FeeSchedule::new(MAX_FEE_BPS, 0, 10, 1), Err(ERROR_INVALID_FEE_SCHEDULE));
+        assert_eq!(FeeSchedule::new(100, 100, 0, 50), Err(ERROR_INVALID_FEE_SCHEDULE));
+
+        // 同样的下注，临近结束时手续费更高
+        let mut market = MarketData::with_liquidity(outcome_text(2), 0, 100_000, 100_000, vec![1_000_000; 2]).unwrap();
+        market.fee_schedule = schedule;
+        let mut near_close = market.clone();
+        let near_close_bps = near_close.fee_rate_bps(OUTCOME_YES, 99_500).unwrap();
+        assert_eq!(market.fee_rate_bps(OUTCOME_YES, 0), Ok(100));
+        assert_eq!(near_close_bps, 200);
+        market.place_bet(OUTCOME_YES, 100_000, 100).unwrap();
+        near_close.place_bet(OUTCOME_YES, 100_000, near_close_bps).unwrap();
+        assert_eq!(market.total_fees_collected + market.referrer_fees, 1000);
+        assert_eq!(near_close.total_fees_collected + near_close.referrer_fees, 2000);
+
+        let mut data = vec![];
+        market.to_data(&mut data);
+        assert_eq!(MarketData::from_data(&mut data.iter_mut()).fee_schedule, schedule);
+    }
+
+    #[test]
+    fn test_fee_split() {
+        use crate::fee::FeeSplit;
+        use crate::player::PlayerData;
+        assert_eq!(FeeSplit::new(5000, 2000, 2000, 1000).unwrap().creator_bps, 2000);
+        assert_eq!(FeeSplit::new(5000, 2000, 2000, 999), Err(ERROR_INVALID_FEE_SPLIT));
+        assert_eq!(FeeSplit::new(u64::MAX, 1, 0, 0), Err(ERROR_INVALID_FEE_SPLIT));
+
+        // 没有流动性提供者时 LP 部分归平台；推荐人部分单独累计，交易者没有推荐人时归平台
+        let mut market = MarketData::with_liquidity(outcome_text(2), 0, 100_000, 100_000, vec![1_000_000; 2]).unwrap();
+        market.fee_split = FeeSplit::new(5000, 2000, 2000, 1000).unwrap();
+        market.place_bet(OUTCOME_YES, 100_000, PLATFORM_FEE_RATE).unwrap();
+        assert_eq!((market.creator_fees, market.lp_fees, market.referrer_fees), (200, 0, 100));
+        assert_eq!(market.total_fees_collected, 700);
+        assert_eq!(market.settle_referrer_share(1000, false), Ok(0));
+        assert_eq!((market.referrer_fees, market.total_fees_collected), (0, 800));
+
+        // 有 LP 份额后四个部分之和等于费用
+        market.total_lp_shares = 1;
+        market.place_bet(OUTCOME_YES, 100_000, PLATFORM_FEE_RATE).unwrap();
+        assert_eq!((market.creator_fees, market.lp_fees, market.referrer_fees), (400, 200, 100));
+        assert_eq!(market.total_fees_collected, 1300);
+
+        // 有推荐人的交易把推荐人部分交给推荐人，由推荐人自己提取
+        let rebate = market.settle_referrer_share(1000, true).unwrap();
+        assert_eq!((rebate, market.referrer_fees, market.total_fees_collected), (100, 0, 1300));
+        let mut referrer = PlayerData::default();
+        referrer.add_referral_earnings(rebate).unwrap();
+        assert_eq!(referrer.withdraw_referral_fees(), Ok(100));
+        assert_eq!(referrer.withdraw_referral_fees(), Err(ERROR_NO_FEES_TO_WITHDRAW));
+        assert_eq!((referrer.balance, referrer.referral_earnings), (100, 100));
+
+        let mut data = vec![];
+        market.to_data(&mut data);
+        let restored = MarketData::from_data(&mut data.iter_mut());
+        assert_eq!(restored.fee_split, market.fee_split);
+        assert_eq!(restored.creator_fees, 400);
+    }
+
+    #[test]
+    fn test_circuit_breaker() {
+        use crate::config::{CIRCUIT_BREAKER_COOLDOWN_TICKS, CIRCUIT_BREAKER_WINDOW_TICKS};
+        let mut market = MarketData::with_liquidity(outcome_text(2), 0, 100_000, 100_000, vec![1_000_000; 2]).unwrap();
+
+        // tick 记录所有结果的参考价格，窗口内小幅变动不触发
+        assert!(market.roll_price_window(10).unwrap());
+        assert_eq!(market.breaker.reference_prices, vec![500_000, 500_000]);
+        market.place_bet(OUTCOME_YES, 100_000, PLATFORM_FEE_RATE).unwrap();
+        assert_eq!(market.check_circuit_breaker(10), Ok(false));
+
+        // 窗口内的 tick 不改变参考价格；窗口结束后以新价格作为参考，同一窗口内累计变动过大则熔断
+        assert!(!market.roll_price_window(11).unwrap());
+        let window_end = 10 + CIRCUIT_BREAKER_WINDOW_TICKS;
+        assert!(market.roll_price_window(window_end).unwrap());
+        let reference = market.breaker.reference_prices.clone();
+        assert!(reference[OUTCOME_YES as usize] > 500_000);
+        market.place_bet(OUTCOME_YES, 400_000, PLATFORM_FEE_RATE).unwrap();
+        assert!(!market.roll_price_window(window_end + 1).unwrap());
+        assert_eq!(market.breaker.reference_prices, reference);
+        market.place_bet(OUTCOME_YES, 400_000, PLATFORM_FEE_RATE).unwrap();
+        assert_eq!(market.check_circuit_breaker(window_end + 1), Ok(true));
+        assert!(market.breaker.is_halted(window_end + CIRCUIT_BREAKER_COOLDOWN_TICKS));
+        assert!(!market.breaker.is_halted(window_end + 1 + CIRCUIT_BREAKER_COOLDOWN_TICKS));
+        assert!(market.breaker.reference_prices.is_empty());
+
+        // 熔断状态写入存储后保持不变
+        let mut data = vec![];
+        market.to_data(&mut data);
+        assert_eq!(MarketData::from_data(&mut data.iter_mut()).breaker, market.breaker);
+
+        // 分类市场监控每个结果的价格：只有结果 0 的变动超过阈值，索引 1 的变动不足
+        let mut categorical = MarketData::with_liquidity(outcome_text(3), 0, 100_000, 100_000, vec![1_000_000; 3]).unwrap();
+        categorical.roll_price_window(10).unwrap();
+        categorical.place_bet(0, 300_000, PLATFORM_FEE_RATE).unwrap();
+        assert_eq!(categorical.check_circuit_breaker(10), Ok(true));
+    }
+
+    #[test]
+    fn test_circuit_breaker_limits() {
+        use crate::circuit_breaker::BreakerLimits;
+        assert_eq!(BreakerLimits::new(0, 10, 10), Err(ERROR_INVALID_BREAKER_LIMITS));
+        assert_eq!(BreakerLimits::new(10_001, 10, 10), Err(ERROR_INVALID_BREAKER_LIMITS));
+        assert_eq!(BreakerLimits::new(100, 0, 10), Err(ERROR_INVALID_BREAKER_LIMITS));
+        assert_eq!(BreakerLimits::new(100, 10, 0), Err(ERROR_INVALID_BREAKER_LIMITS));
+
+        // 阈值按市场设置：收紧后小幅变动也会熔断，冷却期按市场的设置
+        let mut market = MarketData::with_liquidity(outcome_text(2), 0, 100_000, 100_000, vec![1_000_000; 2]).unwrap();
+        market.breaker.limits = BreakerLimits::new(100, 10, 30).unwrap();
+        market.roll_price_window(10).unwrap();
+        market.place_bet(OUTCOME_YES, 100_000, PLATFORM_FEE_RATE).unwrap();
+        assert_eq!(market.check_circuit_breaker(10), Ok(true));
+        assert!(market.breaker.is_halted(39) && !market.breaker.is_halted(40));
+
+        let mut data = vec![];
+        market.to_data(&mut data);
+        assert_eq!(MarketData::from_data(&mut data.iter_mut()).breaker.limits, market.breaker.limits);
+    }
+
+    #[test]
+    fn test_scalar_market_settlement() {
+        let range = ScalarRange { lower: 50_000, upper: 150_000 };
+        let scalar = |state: Vec<u64>, range| {
+            MarketData::with_scalar_range(outcome_text(state.len()), 0, 1000, 1000, PricingModel::Cpmm, state, range)
+        };
+        assert_eq!(scalar(vec![1_000_000; 3], range).err(), Some(ERROR_INVALID_OUTCOME));
+        assert_eq!(scalar(vec![1_000_000; 2], ScalarRange { lower: 10, upper: 10 }).err(), Some(ERROR_INVALID_OUTCOME));
+
+        let mut market = scalar(vec![1_000_000; 2], range).unwrap();
+        let long = market.place_bet(OUTCOME_LONG, 300_000, PLATFORM_FEE_RATE).unwrap();
+        let short = market.place_bet(OUTCOME_SHORT, 100_000, PLATFORM_FEE_RATE).unwrap();
+        let mut holdings = [0; 2];
+        holdings[OUTCOME_LONG as usize] = long;
+        let long_only = holdings;
+        holdings[OUTCOME_SHORT as usize] = short;
+
+        // 解决值位于区间 3/4 处：LONG 分得 75% 奖池，SHORT 分得 25%
+        let mut settled = market.clone();
+        settled.resolve(125_000).unwrap();
+        assert_eq!(settled.outcome, Some(125_000));
+        assert_eq!(settled.calculate_payout(&long_only).unwrap(), market.prize_pool * 3 / 4);
+        assert!(market.prize_pool - settled.calculate_payout(&holdings).unwrap() <= 1);
+
+        // 区间外的解决值截断到边界
+        let mut settled = market.clone();
+        settled.resolve(10).unwrap();
+        assert_eq!(settled.outcome, Some(50_000));
+        assert_eq!(settled.calculate_payout(&long_only), Ok(0));
+
+        // 存储往返保留区间
+        let mut data = vec![];
+        settled.to_data(&mut data);
+        let restored = MarketData::from_data(&mut data.iter_mut());
+        assert_eq!(restored.scalar, Some(range));
+        assert_eq!(restored.outcome, Some(50_000));
+    }
+
+    #[test]
+    fn test_categorical_market_trading() {
+        let mut market = MarketData::with_liquidity(outcome_text(3), 0, 1000, 1000, vec![1_000_000; 3]).unwrap();
+        assert_eq!(market.get_price(0).unwrap(), 333_333);
+
+        // 非法结果索引
+        assert_eq!(market.place_bet(3, 10000, PLATFORM_FEE_RATE), Err(ERROR_INVALID_BET_TYPE));
+        assert_eq!(market.get_price(3), Err(ERROR_INVALID_BET_TYPE));
+
+        let shares = market.place_bet(2, 100_000, PLATFORM_FEE_RATE).unwrap();
+        assert!(shares > 0);
+        market.place_bet(0, 20_000, PLATFORM_FEE_RATE).unwrap();
+
+        // 买入推高价格，价格之和保持为 1（允许取整误差）
+        let prices: Vec<u64> = (0..3).map(|i| market.get_price(i).unwrap()).collect();
+        assert!(prices[2] > prices[0] && prices[0] > prices[1]);
+        let total_price: u64 = prices.iter().sum();
+        assert!(PRICE_PRECISION - total_price < 3);
+
+        // 卖出部分份额
+        let prize_pool = market.prize_pool;
+        let payout = market.sell_shares(2, shares / 2, PLATFORM_FEE_RATE).unwrap();
+        assert!(payout > 0);
+        assert_eq!(market.prize_pool, prize_pool - payout);
+        assert_eq!(market.total_shares[2], shares - shares / 2);
+
+        // 只有获胜结果的持有者获得奖金
+        assert_eq!(market.resolve(3), Err(ERROR_INVALID_OUTCOME));
+        market.resolve(2).unwrap();
+        let payout = market.calculate_payout(&[0, 0, shares - shares / 2]).unwrap();
+        assert_eq!(payout, market.prize_pool);
+        assert_eq!(market.calculate_payout(&[1000, 1000, 0]).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_market_text_roundtrip() {
+        let text = MarketText {
+            title: "BTC 十万美元".to_string(),
+            description: "Will Bitcoin reach $100,000?".to_string(),
+            resolution_criteria: String::new(),
+            outcomes: vec!["No".to_string(), "Yes".to_string()],
+        };
+
+        let mut data = vec![];
+        text.to_data(&mut data);
+        let restored = MarketText::from_data(&mut data.iter_mut());
+        assert_eq!(restored.title, text.title);
+        assert_eq!(restored.description, text.description);
+        assert_eq!(restored.resolution_criteria, text.resolution_criteria);
+        assert_eq!(restored.outcomes, text.outcomes);
+
+        // 交易参数解码与存储格式一致
+        let decoded = MarketText::decode(&data).unwrap();
+        assert_eq!(decoded.title, text.title);
+
+        // 多余参数、截断参数和超长文本都应被拒绝
+        let mut trailing = data.clone();
+        trailing.push(0);
+        assert!(MarketText::decode(&trailing).is_none());
+        assert!(MarketText::decode(&data[..data.len() - 1]).is_none());
+        assert!(MarketText::decode(&[(MAX_MARKET_TEXT_BYTES + 1) as u64, 0, 0]).is_none());
+    }
+
     #[test]
     fn test_safe_bet_amount_limits() {
         let mut market = MarketData::new(
@@ -310,15 +980,15 @@ mod market_safe_tests {
         ).unwrap();
         
         // 测试过大投注
-        let result = market.place_bet(1, MAX_BET_AMOUNT + 1);
+        let result = market.place_bet(1, MAX_BET_AMOUNT + 1, PLATFORM_FEE_RATE);
         assert_eq!(result, Err(ERROR_BET_TOO_LARGE));
         
         // 测试零投注
-        let result = market.place_bet(1, 0);
+        let result = market.place_bet(1, 0, PLATFORM_FEE_RATE);
         assert_eq!(result, Err(ERROR_INVALID_BET_AMOUNT));
         
         // 测试正常投注
-        let result = market.place_bet(1, 1000);
+        let result = market.place_bet(1, 1000, PLATFORM_FEE_RATE);
         assert!(result.is_ok());
     }
 
@@ -355,12 +1025,12 @@ mod market_safe_tests {
         ).unwrap();
         
         // 测试正常份额计算
-        let shares = market.calculate_shares(1, 10000);
+        let shares = market.calculate_shares(1, 10000, PLATFORM_FEE_RATE);
         assert!(shares.is_ok());
         assert!(shares.unwrap() > 0);
         
         // 测试过大投注
-        let shares = market.calculate_shares(1, MAX_BET_AMOUNT + 1);
+        let shares = market.calculate_shares(1, MAX_BET_AMOUNT + 1, PLATFORM_FEE_RATE);
         assert_eq!(shares, Err(ERROR_BET_TOO_LARGE));
     }
 
@@ -375,18 +1045,18 @@ mod market_safe_tests {
         ).unwrap();
         
         // 先投注获得份额
-        let shares = market.place_bet(1, 10000).unwrap();
+        let shares = market.place_bet(1, 10000, PLATFORM_FEE_RATE).unwrap();
         
         // 测试正常卖出
-        let payout = market.sell_shares(1, shares / 2);
+        let payout = market.sell_shares(1, shares / 2, PLATFORM_FEE_RATE);
         assert!(payout.is_ok());
         
         // 测试卖出过多份额
-        let payout = market.sell_shares(1, shares * 2);
+        let payout = market.sell_shares(1, shares * 2, PLATFORM_FEE_RATE);
         assert_eq!(payout, Err(ERROR_INSUFFICIENT_BALANCE));
         
         // 测试过大份额数
-        let payout = market.sell_shares(1, MAX_SHARES + 1);
+        let payout = market.sell_shares(1, MAX_SHARES + 1, PLATFORM_FEE_RATE);
         assert_eq!(payout, Err(ERROR_BET_TOO_LARGE));
     }
 
@@ -401,18 +1071,408 @@ mod market_safe_tests {
         ).unwrap();
         
         // 投注并解决市场
-        let yes_shares = market.place_bet(1, 10000).unwrap();
-        let no_shares = market.place_bet(0, 5000).unwrap();
+        let yes_shares = market.place_bet(1, 10000, PLATFORM_FEE_RATE).unwrap();
+        let no_shares = market.place_bet(0, 5000, PLATFORM_FEE_RATE).unwrap();
         
-        market.resolve(true).unwrap(); // YES 获胜
+        market.resolve(OUTCOME_YES).unwrap(); // YES 获胜
         
         // 测试奖金计算
-        let payout = market.calculate_payout(yes_shares, 0);
+        let payout = market.calculate_payout(&[0, yes_shares]);
         assert!(payout.is_ok());
         assert!(payout.unwrap() > 0);
         
         // NO持有者应该没有奖金
-        let payout = market.calculate_payout(0, no_shares);
+        let payout = market.calculate_payout(&[no_shares, 0]);
         assert_eq!(payout.unwrap(), 0);
     }
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod player_position_tests {
+    use zkwasm_rest_abi::StorageData;
+    use crate::player::PlayerData;
+    use crate::error::*;
+
+    #[test]
+    fn test_positions_are_per_market() {
+        let mut player = PlayerData::default();
+        player.add_shares(0, 1, 100);
+        player.add_shares(3, 0, 50);
+
+        assert_eq!(player.position(0).unwrap().shares_of(1), 100);
+        assert_eq!(player.position(3).unwrap().shares_of(0), 50);
+        assert!(player.position(1).is_none());
+
+        // 不能卖出其他市场或其他结果的份额
+        assert_eq!(player.spend_shares(3, 1, 1), Err(ERROR_INSUFFICIENT_BALANCE));
+        assert_eq!(player.spend_shares(0, 5, 1), Err(ERROR_INSUFFICIENT_BALANCE));
+        assert!(player.spend_shares(0, 1, 100).is_ok());
+
+        // 每个市场单独领取
+        assert!(player.claim_winnings(0).is_ok());
+        assert_eq!(player.claim_winnings(0), Err(ERROR_ALREADY_CLAIMED));
+        assert!(player.claim_winnings(3).is_ok());
+    }
+
+    #[test]
+    fn test_credit_balance() {
+        let mut player = PlayerData::default();
+        player.credit_balance(u64::MAX - 10).unwrap();
+        // 余额溢出时拒绝入账
+        assert_eq!(player.credit_balance(11), Err(ERROR_OVERFLOW));
+        assert_eq!(player.balance, u64::MAX - 10);
+    }
+
+    #[test]
+    fn test_transfer_shares_out() {
+        let mut player = PlayerData::default();
+        player.add_shares(2, 0, 300);
+        player.add_shares(2, 1, 100);
+        player.add_outcome_cost_basis(2, 0, 60);
+        player.add_outcome_cost_basis(2, 1, 140);
+        player.add_set_shares(2, 2, 50);
+        player.add_cost_basis(2, 50);
+
+        // 成本只按转出份额占该结果可用份额的比例转出，不受其他结果和份额组影响
+        assert_eq!(player.transfer_shares_out(2, 1, 101), Err(ERROR_INSUFFICIENT_BALANCE));
+        assert_eq!(player.transfer_shares_out(2, 1, 100), Ok(140));
+        assert_eq!(player.position(2).unwrap().shares, vec![350, 50]);
+        assert_eq!(player.position(2).unwrap().cost_basis, 110);
+
+        // 卖出收益先冲减该结果的成本
+        assert_eq!(player.reduce_outcome_cost_basis(2, 0, 20), 20);
+        assert_eq!(player.transfer_shares_out(2, 0, 150), Ok(20));
+        assert_eq!(player.position(2).unwrap().outcome_cost_basis, vec![20, 0]);
+        assert_eq!(player.position(2).unwrap().cost_basis, 70);
+    }
+
+    #[test]
+    fn test_sale_above_outcome_basis() {
+        let mut player = PlayerData::default();
+        player.add_shares(2, 0, 100);
+        player.add_shares(2, 1, 100);
+        player.add_outcome_cost_basis(2, 0, 30);
+        player.add_outcome_cost_basis(2, 1, 70);
+        player.add_set_shares(2, 2, 10);
+        player.add_cost_basis(2, 10);
+
+        // 卖出结果 0 的收益超过其成本，只冲减结果 0 的成本，其他结果和份额组的成本不变
+        assert_eq!(player.reduce_outcome_cost_basis(2, 0, 80), 30);
+        assert_eq!(player.position(2).unwrap().outcome_cost_basis, vec![0, 70]);
+        assert_eq!(player.position(2).unwrap().cost_basis, 80);
+
+        // 之后转出结果 1 时仍带走其全部成本
+        assert_eq!(player.transfer_shares_out(2, 1, 100), Ok(70));
+        assert_eq!(player.position(2).unwrap().cost_basis, 10);
+    }
+
+    #[test]
+    fn test_minted_sets_only_redeem_as_sets() {
+        use crate::config::PLATFORM_FEE_RATE;
+        use crate::market::MarketData;
+        use super::market_safe_tests::outcome_text;
+        let mut market = MarketData::with_liquidity(outcome_text(2), 0, 1000, 1000, vec![1_000_000; 2]).unwrap();
+        // 其他玩家推高 YES 价格
+        market.place_bet(1, 500_000, PLATFORM_FEE_RATE).unwrap();
+
+        // AMM 对两边份额的报价之和高于一组的成本
+        let paid = 10_000;
+        market.mint_set(paid).unwrap();
+        let mut player = PlayerData::default();
+        player.add_set_shares(0, 2, paid);
+        let quoted: u64 = (0..2).map(|outcome| market.calculate_sell_details(outcome, paid, PLATFORM_FEE_RATE).unwrap().0).sum();
+        assert!(quoted > paid);
+
+        // 铸造后卖出两边，收回的金额不超过投入
+        let mut proceeds = 0;
+        for outcome in 0..2 {
+            if player.spend_shares(0, outcome, paid).is_ok() {
+                proceeds += market.sell_shares(outcome, paid, PLATFORM_FEE_RATE).unwrap();
+            }
+        }
+        assert!(proceeds <= paid);
+        assert_eq!(player.transfer_shares_out(0, 1, 1), Err(ERROR_INSUFFICIENT_BALANCE));
+
+        // 之后买入的份额仍可卖出
+        player.add_shares(0, 1, 100);
+        assert!(player.spend_shares(0, 1, 100).is_ok());
+        assert_eq!(player.spend_shares(0, 1, 1), Err(ERROR_INSUFFICIENT_BALANCE));
+
+        // 只能整组赎回，按成本取回抵押品
+        assert_eq!(player.spend_set_shares(0, 2, paid + 1), Err(ERROR_INSUFFICIENT_BALANCE));
+        player.spend_set_shares(0, 2, paid).unwrap();
+        let pool = market.prize_pool;
+        market.redeem_set(paid).unwrap();
+        assert_eq!(pool - market.prize_pool, paid);
+        assert_eq!(player.position(0).unwrap().set_shares, 0);
+
+        let mut data = vec![];
+        player.add_set_shares(0, 2, 7);
+        player.to_data(&mut data);
+        assert_eq!(PlayerData::from_data(&mut data.iter_mut()).position(0).unwrap().set_shares, 7);
+    }
+
+    #[test]
+    fn test_position_limit() {
+        use crate::config::MAX_POSITION_SHARES;
+        let mut player = PlayerData::default();
+        assert!(player.check_position_limit(0, 1).is_ok());
+
+        // 上限按结果分别计算
+        player.add_shares(0, 1, MAX_POSITION_SHARES);
+        player.add_shares(0, 0, MAX_POSITION_SHARES);
+        assert!(player.check_position_limit(0, 1).is_ok());
+        player.add_shares(0, 1, 1);
+        assert_eq!(player.check_position_limit(0, 1), Err(ERROR_POSITION_LIMIT_EXCEEDED));
+        assert!(player.check_position_limit(0, 0).is_ok());
+    }
+
+    #[test]
+    fn test_volume_fee_tiers() {
+        use crate::fee::discounted_rate_bps;
+        let mut player = PlayerData::default();
+        assert_eq!(player.fee_tier(), 0);
+        assert_eq!(discounted_rate_bps(100, 0), 100);
+
+        // 累计交易量达到门槛后进入下一档
+        player.record_trade(9_999_999, 100_000).unwrap();
+        assert_eq!(player.fee_tier(), 0);
+        player.record_trade(1, 0).unwrap();
+        assert_eq!(player.fee_tier(), 1);
+        assert_eq!(discounted_rate_bps(100, 1), 90);
+        player.record_trade(2_000_000_000, 0).unwrap();
+        assert_eq!(player.fee_tier(), 3);
+        // 折扣后的费率向下取整
+        assert_eq!(discounted_rate_bps(101, 3), 50);
+    }
+
+    #[test]
+    fn test_referrer() {
+        let mut player = PlayerData::default();
+        // 不能推荐自己，推荐人只能设置一次
+        assert_eq!(player.set_referrer(&[1, 1], [1, 1]), Err(ERROR_INVALID_REFERRER));
+        player.set_referrer(&[1, 1], [2, 2]).unwrap();
+        assert_eq!(player.set_referrer(&[1, 1], [3, 3]), Err(ERROR_INVALID_REFERRER));
+        assert_eq!(player.referrer, Some([2, 2]));
+
+        let mut referrer = PlayerData::default();
+        referrer.add_referral_earnings(40).unwrap();
+        assert_eq!((referrer.balance, referrer.referral_fees, referrer.referral_earnings), (0, 40, 40));
+    }
+
+    #[test]
+    fn test_player_data_storage_roundtrip() {
+        let mut player = PlayerData::default();
+        player.add_balance(1000);
+        player.add_shares(0, 1, 100);
+        player.add_shares(7, 4, 25);
+        player.add_cost_basis(7, 30);
+        assert_eq!(player.reduce_cost_basis(7, 50), 30);
+        player.add_outcome_cost_basis(0, 1, 120);
+        player.record_trade(500, 5).unwrap();
+        player.set_referrer(&[1, 1], [2, 3]).unwrap();
+        player.referral_count = 4;
+
+        let mut data = vec![];
+        player.to_data(&mut data);
+        let restored = PlayerData::from_data(&mut data.iter_mut());
+
+        assert_eq!(restored.balance, 1000);
+        assert_eq!((restored.traded_volume, restored.fees_paid), (500, 5));
+        assert_eq!((restored.referrer, restored.referral_count), (Some([2, 3]), 4));
+        assert_eq!(restored.positions.len(), 2);
+        assert_eq!(restored.position(0).unwrap().shares, vec![0, 100]);
+        assert_eq!(restored.position(7).unwrap().shares_of(4), 25);
+        assert_eq!(restored.position(0).unwrap().cost_basis, 120);
+        assert_eq!(restored.position(0).unwrap().outcome_cost_basis, vec![0, 120]);
+        assert_eq!(restored.position(7).unwrap().cost_basis, 0);
+    }
+
+    #[test]
+    fn test_locked_balance() {
+        let mut player = PlayerData::default();
+        player.add_balance(1000);
+
+        assert_eq!(player.lock_balance(1001), Err(ERROR_INSUFFICIENT_BALANCE));
+        assert!(player.lock_balance(600).is_ok());
+        assert_eq!((player.balance, player.locked_balance), (400, 600));
+
+        // 撤单退回，成交消耗
+        player.unlock_balance(200);
+        player.spend_locked_balance(400);
+        assert_eq!((player.balance, player.locked_balance), (600, 0));
+
+        player.lock_balance(100).unwrap();
+        let mut data = vec![];
+        player.to_data(&mut data);
+        let restored = PlayerData::from_data(&mut data.iter_mut());
+        assert_eq!((restored.balance, restored.locked_balance), (500, 100));
+    }
+}
+
+#[cfg(test)]
+mod limit_order_tests {
+    use zkwasm_rest_abi::StorageData;
+    use crate::order::{LimitOrder, OrderBook};
+    use crate::error::*;
+
+    fn order(order_id: u64, pid: [u64; 2]) -> LimitOrder {
+        LimitOrder {
+            order_id,
+            pid,
+            outcome: 1,
+            limit_price: 350_000,
+            amount: 1000,
+        }
+    }
+
+    #[test]
+    fn test_limit_order_trigger_and_roundtrip() {
+        let order = order(3, [1, 2]);
+        assert!(order.is_triggered(350_000));
+        assert!(order.is_triggered(100_000));
+        assert!(!order.is_triggered(350_001));
+
+        let mut data = vec![];
+        order.to_data(&mut data);
+        let restored = LimitOrder::from_data(&mut data.iter_mut());
+        assert_eq!(restored.order_id, 3);
+        assert_eq!(restored.pid, [1, 2]);
+        assert_eq!(restored.limit_price, 350_000);
+        assert_eq!(restored.amount, 1000);
+    }
+
+    #[test]
+    fn test_only_owner_can_remove_order() {
+        let mut book = OrderBook {
+            market_id: 0,
+            orders: vec![order(0, [1, 2]), order(1, [3, 4])],
+        };
+
+        assert_eq!(book.remove(0, &[3, 4]).err(), Some(ERROR_ORDER_NOT_EXIST));
+        assert_eq!(book.remove(5, &[1, 2]).err(), Some(ERROR_ORDER_NOT_EXIST));
+        assert_eq!(book.remove(0, &[1, 2]).unwrap().order_id, 0);
+        assert_eq!(book.orders.len(), 1);
+        assert_eq!(book.orders[0].order_id, 1);
+    }
+
+    #[test]
+    fn test_large_order_against_thin_pool() {
+        use crate::config::PLATFORM_FEE_RATE;
+        use crate::market::MarketData;
+        use super::market_safe_tests::outcome_text;
+        let mut market = MarketData::with_liquidity(outcome_text(2), 0, 1000, 1000, vec![100_000, 100_000]).unwrap();
+        let order = LimitOrder { amount: 100_000, limit_price: 600_000, ..order(0, [1, 2]) };
+        assert!(order.is_triggered(market.get_yes_price().unwrap()));
+
+        // Filling in full would pay far more than the limit per share
+        let full = market.calculate_shares(1, order.amount, PLATFORM_FEE_RATE).unwrap();
+        assert!(full < order.min_shares_out(&market, order.amount).unwrap());
+
+        // Only the part within the limit fills, and a little more would not
+        let amount = order.fillable_amount(&market, PLATFORM_FEE_RATE);
+        assert!(amount > 0 && amount < order.amount);
+        let over = market.calculate_shares(1, amount + 1, PLATFORM_FEE_RATE).unwrap();
+        assert!(over < order.min_shares_out(&market, amount + 1).unwrap());
+        let shares = market.place_bet(1, amount, PLATFORM_FEE_RATE).unwrap();
+        assert!(shares >= order.min_shares_out(&market, amount).unwrap());
+
+        // With the price past the limit the rest keeps resting
+        let rest = LimitOrder { amount: order.amount - amount, ..order };
+        assert_eq!(rest.fillable_amount(&market, PLATFORM_FEE_RATE), 0);
+    }
+}
+
+#[cfg(test)]
+mod dispute_tests {
+    use zkwasm_rest_abi::StorageData;
+    use crate::dispute::DisputeBond;
+
+    #[test]
+    fn test_dispute_bond_settlement() {
+        let dispute = DisputeBond { market_id: 3, disputer: [2, 2], outcome: 1, bond: 100_000 };
+
+        // 争议改变了结果则退回保证金，否则全部归入国库
+        assert_eq!(dispute.settle(0), (100_000, 0));
+        assert_eq!(dispute.settle(1), (0, 100_000));
+
+        let mut data = vec![];
+        dispute.to_data(&mut data);
+        let restored = DisputeBond::from_data(&mut data.iter_mut());
+        assert_eq!((restored.disputer, restored.outcome, restored.bond), ([2, 2], 1, 100_000));
+    }
+}
+
+#[cfg(test)]
+mod resolver_tests {
+    use zkwasm_rest_abi::StorageData;
+    use crate::resolver::{ResolverCommittee, MAX_RESOLVERS};
+    use crate::error::*;
+
+    #[test]
+    fn test_committee_validation() {
+        let members = vec![[1, 1], [2, 2], [3, 3]];
+        assert!(ResolverCommittee::new(0, members.clone(), 2).is_ok());
+        assert_eq!(ResolverCommittee::new(0, members.clone(), 0).err(), Some(ERROR_INVALID_COMMITTEE));
+        assert_eq!(ResolverCommittee::new(0, members, 4).err(), Some(ERROR_INVALID_COMMITTEE));
+        assert_eq!(ResolverCommittee::new(0, vec![[1, 1], [1, 1]], 1).err(), Some(ERROR_INVALID_COMMITTEE));
+        let too_many = (0..=MAX_RESOLVERS as u64).map(|i| [i, i]).collect();
+        assert_eq!(ResolverCommittee::new(0, too_many, 1).err(), Some(ERROR_INVALID_COMMITTEE));
+    }
+
+    #[test]
+    fn test_committee_votes() {
+        let mut committee = ResolverCommittee::new(4, vec![[1, 1], [2, 2], [3, 3]], 2).unwrap();
+        assert_eq!(committee.vote(&[9, 9], 1), Err(ERROR_UNAUTHORIZED));
+
+        // 成员可以改票，只统计相同结果的票数
+        assert_eq!(committee.vote(&[1, 1], 0), Ok(1));
+        assert_eq!(committee.vote(&[2, 2], 1), Ok(1));
+        assert_eq!(committee.vote(&[1, 1], 1), Ok(2));
+
+        let mut data = vec![];
+        committee.to_data(&mut data);
+        let restored = ResolverCommittee::from_data(&mut data.iter_mut());
+        assert_eq!(restored.threshold, 2);
+        assert_eq!(restored.members, committee.members);
+        assert_eq!(restored.votes, vec![Some(1), Some(1), None]);
+
+        committee.clear_votes();
+        assert_eq!(committee.vote(&[3, 3], 1), Ok(1));
+    }
+}
+
+#[cfg(test)]
+mod proposal_tests {
+    use zkwasm_rest_abi::StorageData;
+    use crate::proposal::ResolutionProposal;
+    use crate::error::*;
+
+    fn proposal(challenger: Option<[u64; 2]>) -> ResolutionProposal {
+        ResolutionProposal { market_id: 3, proposer: [1, 1], outcome: 1, bond: 100_000, challenger }
+    }
+
+    #[test]
+    fn test_unchallenged_proposal_settlement() {
+        // 无人挑战：结果正确退回保证金，被管理员更正则全部归入国库
+        assert_eq!(proposal(None).settle(1), Ok((vec![([1, 1], 100_000)], 0)));
+        assert_eq!(proposal(None).settle(0), Ok((vec![], 100_000)));
+    }
+
+    #[test]
+    fn test_challenged_proposal_settlement() {
+        let mut challenged = proposal(None);
+        assert_eq!(challenged.challenge(&[1, 1]), Err(ERROR_UNAUTHORIZED));
+        challenged.challenge(&[2, 2]).unwrap();
+        assert_eq!(challenged.challenge(&[3, 3]), Err(ERROR_UNAUTHORIZED));
+
+        // 胜方取回保证金并获得败方保证金扣除国库份额后的部分
+        assert_eq!(challenged.settle(1), Ok((vec![([1, 1], 150_000)], 50_000)));
+        assert_eq!(challenged.settle(0), Ok((vec![([2, 2], 150_000)], 50_000)));
+
+        let mut data = vec![];
+        challenged.to_data(&mut data);
+        let restored = ResolutionProposal::from_data(&mut data.iter_mut());
+        assert_eq!((restored.proposer, restored.outcome, restored.bond), ([1, 1], 1, 100_000));
+        assert_eq!(restored.challenger, Some([2, 2]));
+    }
+}
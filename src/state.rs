@@ -1,11 +1,12 @@
 use serde::Serialize;
 use zkwasm_rest_abi::{StorageData, MERKLE_MAP};
 use std::cell::RefCell;
-use crate::market::MarketData;
+use crate::market::{Market, MarketData, MarketStatus, MarketText, Pause, ScalarRange};
+use crate::order::OrderBook;
+use crate::proposal::ResolutionProposal;
+use crate::resolver::ResolverCommittee;
 use crate::config::DEFAULT_MARKET;
-use crate::error::ERROR_MARKET_NOT_ACTIVE;
-use crate::event::{MarketEvent, EVENT_MARKET_UPDATE};
-use crate::event::insert_event;
+use crate::error::{ERROR_MARKET_NOT_ACTIVE, ERROR_MARKET_PAUSED, ERROR_TRADING_HALTED};
 
 #[derive(Serialize)]
 pub struct QueryState {
@@ -16,44 +17,149 @@ pub struct QueryState {
 #[derive(Serialize, Clone)]
 pub struct GlobalState {
     pub counter: u64,
-    pub market: MarketData,
     pub total_players: u64,
     pub txsize: u64,
     pub txcounter: u64,
+    pub market_count: u64, // markets are numbered 0..market_count
+    pub order_counter: u64, // id of the next limit order
+    pub order_markets: Vec<u64>, // markets with resting limit orders, matched on tick
+    pub live_markets: Vec<u64>, // markets not yet finalized or cancelled, advanced on tick
+    pub pause: Pause, // admin kill switch for every market
+    pub halted_markets: Vec<u64>, // markets halted by their circuit breaker, checked on tick
+}
+
+#[derive(Serialize)]
+struct StateSnapshot<'a> {
+    #[serde(flatten)]
+    state: &'a GlobalState,
+    markets: Vec<Market>,
+    order_books: Vec<OrderBook>,
+    resolver_committees: Vec<ResolverCommittee>,
+    resolution_proposals: Vec<ResolutionProposal>,
 }
 
 impl GlobalState {
     pub fn new() -> Self {
-        // Create market using config parameters
-        let market = MarketData::new(
-            DEFAULT_MARKET.title.to_string(),
-            DEFAULT_MARKET.description.to_string(),
-            DEFAULT_MARKET.start_time,
-            DEFAULT_MARKET.end_time,
-            DEFAULT_MARKET.resolution_time,
-        ).expect("Failed to create market");
-
         GlobalState {
             counter: 0,
-            market,
             total_players: 0,
             txsize: 0,
             txcounter: 0,
+            market_count: 0,
+            order_counter: 0,
+            order_markets: vec![],
+            live_markets: vec![],
+            pause: Pause::default(),
+            halted_markets: vec![],
         }
     }
 
-    fn emit_market_event(&self) {
-        let market_event = MarketEvent::from(&self.market);
-        let mut data = Vec::with_capacity(3);
-        data.push(self.counter);
-        market_event.to_data(&mut data);
-        insert_event(EVENT_MARKET_UPDATE, &mut data);
+    // Register a market under the next free id
+    pub fn add_market(&mut self, data: MarketData) -> Market {
+        let market = Market {
+            market_id: self.market_count,
+            data,
+        };
+        market.store();
+        market.store_text();
+        self.live_markets.push(market.market_id);
+        self.market_count += 1;
+        market
+    }
+
+    pub fn next_order_id(&mut self) -> u64 {
+        let order_id = self.order_counter;
+        self.order_counter += 1;
+        order_id
+    }
+
+    pub fn track_order_market(&mut self, market_id: u64) {
+        if !self.order_markets.contains(&market_id) {
+            self.order_markets.push(market_id);
+        }
+    }
+
+    pub fn untrack_order_market(&mut self, market_id: u64) {
+        self.order_markets.retain(|id| *id != market_id);
+    }
+
+    pub fn untrack_live_market(&mut self, market_id: u64) {
+        self.live_markets.retain(|id| *id != market_id);
+    }
+
+    /// Step every live market through its time-driven transitions; called once per tick.
+    /// Each transition is stored and reported with its own MARKET_STATUS event, so a market
+    /// that opens and closes within one tick still reports both. Open markets then snapshot
+    /// their circuit breaker reference prices and report their pricing state in a
+    /// MARKET_UPDATE event, once per tick.
+    fn advance_live_markets(counter: u64) {
+        use crate::command::Activity;
+
+        let market_ids = GLOBAL_STATE.0.borrow().live_markets.clone();
+        for market_id in market_ids {
+            let mut market = match Market::get(market_id) {
+                Some(market) => market,
+                None => continue,
+            };
+            let mut changed = false;
+            while market.data.step(counter) {
+                Activity::emit_status_event(&market, counter);
+                changed = true;
+            }
+            let open = market.data.status == MarketStatus::Open;
+            if open {
+                // Cannot fail: the pricing state was validated when it was stored
+                changed |= market.data.roll_price_window(counter).unwrap();
+            }
+            if changed {
+                market.store();
+            }
+            // Commands finalize or cancel markets too; either way they have nothing left to advance
+            if market.data.is_settled() {
+                GLOBAL_STATE.0.borrow_mut().untrack_live_market(market_id);
+            }
+            if open {
+                Activity::emit_market_event(&market, counter);
+            }
+        }
     }
 
+    pub fn track_halted_market(&mut self, market_id: u64) {
+        if !self.halted_markets.contains(&market_id) {
+            self.halted_markets.push(market_id);
+        }
+    }
+
+    pub fn untrack_halted_market(&mut self, market_id: u64) {
+        self.halted_markets.retain(|id| *id != market_id);
+    }
 
     pub fn snapshot() -> String {
         let state = GLOBAL_STATE.0.borrow();
-        serde_json::to_string(&*state).unwrap()
+        let markets = (0..state.market_count)
+            .filter_map(Market::get)
+            .map(|mut market| {
+                // Report time-driven transitions that are not yet stored
+                market.data.advance(state.counter);
+                market
+            })
+            .collect();
+        let order_books = state.order_markets.iter()
+            .map(|market_id| OrderBook::get(*market_id))
+            .collect();
+        let resolver_committees = (0..state.market_count)
+            .filter_map(ResolverCommittee::get)
+            .collect();
+        let resolution_proposals = (0..state.market_count)
+            .filter_map(ResolutionProposal::get)
+            .collect();
+        serde_json::to_string(&StateSnapshot {
+            state: &state,
+            markets,
+            order_books,
+            resolver_committees,
+            resolution_proposals,
+        }).unwrap()
     }
 
     pub fn get_state(pid: Vec<u64>) -> String {
@@ -62,15 +168,34 @@ impl GlobalState {
         serde_json::to_string(&player).unwrap()
     }
 
-    pub fn ensure_active(&self) -> Result <u64, u32> {
+    // Trading is halted while either the market or every market is paused
+    pub fn is_trading_paused(&self, market: &MarketData) -> bool {
+        self.pause.trading || market.pause.trading
+    }
+
+    pub fn ensure_active(&self, market: &MarketData) -> Result <u64, u32> {
         let current_time = self.counter;
-        if !self.market.is_active(current_time) {
+        if self.is_trading_paused(market) {
+            return Err(ERROR_MARKET_PAUSED);
+        }
+        if market.breaker.is_halted(current_time) {
+            return Err(ERROR_TRADING_HALTED);
+        }
+        if !market.is_active(current_time) {
             return Err(ERROR_MARKET_NOT_ACTIVE);
         } else {
             Ok(current_time)
         }
     }
 
+    // Claims stay open during a pause unless the admin also halted them
+    pub fn ensure_claimable(&self, market: &MarketData) -> Result<(), u32> {
+        if self.pause.claims || market.pause.claims {
+            return Err(ERROR_MARKET_PAUSED);
+        }
+        Ok(())
+    }
+
     pub fn preempt() -> bool {
         let mut state = GLOBAL_STATE.0.borrow_mut();
         let counter = state.counter;
@@ -105,6 +230,21 @@ impl GlobalState {
         if !data.is_empty() {
             let mut u64data = data.iter_mut();
             *GLOBAL_STATE.0.borrow_mut() = Self::from_data(&mut u64data);
+        } else {
+            // Fresh state: seed the registry with the configured default market
+            let market = MarketData::with_liquidity(
+                MarketText {
+                    title: DEFAULT_MARKET.title.to_string(),
+                    description: DEFAULT_MARKET.description.to_string(),
+                    resolution_criteria: DEFAULT_MARKET.resolution_criteria.to_string(),
+                    outcomes: vec!["No".to_string(), "Yes".to_string()],
+                },
+                DEFAULT_MARKET.start_time,
+                DEFAULT_MARKET.end_time,
+                DEFAULT_MARKET.resolution_time,
+                vec![DEFAULT_MARKET.initial_no_liquidity, DEFAULT_MARKET.initial_yes_liquidity],
+            ).expect("Failed to create market");
+            GLOBAL_STATE.0.borrow_mut().add_market(market);
         }
     }
 
@@ -120,7 +260,21 @@ impl StorageData for GlobalState {
             total_players: *u64data.next().unwrap(),
             txsize: *u64data.next().unwrap(),
             txcounter: *u64data.next().unwrap(),
-            market: MarketData::from_data(u64data),
+            market_count: *u64data.next().unwrap(),
+            order_counter: *u64data.next().unwrap(),
+            order_markets: {
+                let count = *u64data.next().unwrap();
+                (0..count).map(|_| *u64data.next().unwrap()).collect()
+            },
+            live_markets: {
+                let count = *u64data.next().unwrap();
+                (0..count).map(|_| *u64data.next().unwrap()).collect()
+            },
+            pause: Pause::from_code(*u64data.next().unwrap()),
+            halted_markets: {
+                let count = *u64data.next().unwrap();
+                (0..count).map(|_| *u64data.next().unwrap()).collect()
+            },
         }
     }
 
@@ -129,7 +283,15 @@ impl StorageData for GlobalState {
         data.push(self.total_players);
         data.push(self.txsize);
         data.push(self.txcounter);
-        self.market.to_data(data);
+        data.push(self.market_count);
+        data.push(self.order_counter);
+        data.push(self.order_markets.len() as u64);
+        data.extend_from_slice(&self.order_markets);
+        data.push(self.live_markets.len() as u64);
+        data.extend_from_slice(&self.live_markets);
+        data.push(self.pause.code());
+        data.push(self.halted_markets.len() as u64);
+        data.extend_from_slice(&self.halted_markets);
     }
 }
 
@@ -150,6 +312,31 @@ const SELL: u64 = 5;
 const RESOLVE: u64 = 6;
 const CLAIM: u64 = 7;
 const WITHDRAW_FEES: u64 = 8;
+const CREATE_MARKET: u64 = 9;
+const BUY_SHARES: u64 = 10;
+const PLACE_ORDER: u64 = 11;
+const CANCEL_ORDER: u64 = 12;
+const ADD_LIQUIDITY: u64 = 13;
+const REMOVE_LIQUIDITY: u64 = 14;
+const CANCEL_MARKET: u64 = 15;
+const CREATE_SCALAR_MARKET: u64 = 16;
+const DISPUTE: u64 = 17;
+const SET_RESOLVERS: u64 = 18;
+const VOTE_RESOLUTION: u64 = 19;
+const PROPOSE_OUTCOME: u64 = 20;
+const SETTLE_PROPOSAL: u64 = 21;
+const PAUSE: u64 = 22;
+const UNPAUSE: u64 = 23;
+const SET_CIRCUIT_BREAKER: u64 = 24;
+const MINT_SET: u64 = 25;
+const REDEEM_SET: u64 = 26;
+const TRANSFER_SHARES: u64 = 27;
+const TRANSFER: u64 = 28;
+const SET_FEE_SCHEDULE: u64 = 29;
+const SET_FEE_SPLIT: u64 = 30;
+const WITHDRAW_CREATOR_FEES: u64 = 31;
+const SET_REFERRER: u64 = 32;
+const WITHDRAW_REFERRAL_FEES: u64 = 33;
 
 pub struct Transaction {
     command: crate::command::Command,
@@ -162,7 +349,11 @@ impl Transaction {
     }
 
     pub fn decode(params: &[u64]) -> Self {
-        use crate::command::{Command, Activity, Withdraw, Deposit};
+        use crate::command::{Command, Activity, CreateMarket, Withdraw, Deposit, Transfer};
+        use crate::math_safe::MAX_OUTCOMES;
+        use crate::player::Player;
+        use crate::pricing::PricingModel;
+        use crate::resolver::MAX_RESOLVERS;
         use zkwasm_rest_abi::enforce;
         
         let command = params[0] & 0xff;
@@ -179,19 +370,146 @@ impl Transaction {
             Command::Deposit(Deposit {
                 data: [params[1], params[2], params[4]]
             })
+        } else if command == TRANSFER {
+            enforce(params.len() == 4, "transfer needs 4 params");
+            Command::Transfer(Transfer {
+                to: [params[1], params[2]],
+                amount: params[3],
+            })
         } else if command == BET {
-            enforce(params.len() == 3, "bet needs 3 params");
-            Command::Activity(Activity::Bet(params[1], params[2]))
+            enforce(params.len() == 5, "bet needs 5 params");
+            Command::Activity(Activity::Bet(params[1], params[2], params[3], params[4]))
         } else if command == SELL {
-            enforce(params.len() == 3, "sell needs 3 params");
-            Command::Activity(Activity::Sell(params[1], params[2]))
+            enforce(params.len() == 5, "sell needs 5 params");
+            Command::Activity(Activity::Sell(params[1], params[2], params[3], params[4]))
         } else if command == RESOLVE {
-            enforce(params.len() == 2, "resolve needs 2 params");
-            Command::Activity(Activity::Resolve(params[1]))
+            enforce(params.len() == 3, "resolve needs 3 params");
+            Command::Activity(Activity::Resolve(params[1], params[2]))
         } else if command == CLAIM {
-            Command::Activity(Activity::Claim)
+            enforce(params.len() == 2, "claim needs 2 params");
+            Command::Activity(Activity::Claim(params[1]))
         } else if command == WITHDRAW_FEES {
-            Command::Activity(Activity::WithdrawFees)
+            enforce(params.len() == 2, "withdraw fees needs 2 params");
+            Command::Activity(Activity::WithdrawFees(params[1]))
+        } else if command == CREATE_MARKET {
+            enforce(params.len() >= 7, "create market needs at least 7 params");
+            // Times, the pricing model and its parameter, then the outcome count and
+            // per-outcome pricing state, then the packed market text
+            let pricing = PricingModel::from_params(params[4], params[5]);
+            enforce(pricing.is_some(), "invalid pricing model");
+            let outcome_count = params[6] as usize;
+            enforce(outcome_count <= MAX_OUTCOMES, "too many outcomes");
+            enforce(params.len() >= 7 + outcome_count, "missing outcome liquidity");
+            let text = MarketText::decode(&params[7 + outcome_count..]);
+            enforce(text.is_some(), "invalid market text");
+            Command::Activity(Activity::CreateMarket(CreateMarket {
+                start_time: params[1],
+                end_time: params[2],
+                resolution_time: params[3],
+                pricing: pricing.unwrap(),
+                liquidity: params[7..7 + outcome_count].to_vec(),
+                text: text.unwrap(),
+                scalar: None,
+            }))
+        } else if command == CREATE_SCALAR_MARKET {
+            enforce(params.len() >= 10, "create scalar market needs at least 10 params");
+            // Times, the pricing model and its parameter, the value range, then the
+            // [SHORT, LONG] pricing state and the packed market text
+            let pricing = PricingModel::from_params(params[4], params[5]);
+            enforce(pricing.is_some(), "invalid pricing model");
+            let text = MarketText::decode(&params[10..]);
+            enforce(text.is_some(), "invalid market text");
+            Command::Activity(Activity::CreateMarket(CreateMarket {
+                start_time: params[1],
+                end_time: params[2],
+                resolution_time: params[3],
+                pricing: pricing.unwrap(),
+                liquidity: params[8..10].to_vec(),
+                text: text.unwrap(),
+                scalar: Some(ScalarRange {
+                    lower: params[6],
+                    upper: params[7],
+                }),
+            }))
+        } else if command == BUY_SHARES {
+            enforce(params.len() == 5, "buy shares needs 5 params");
+            Command::Activity(Activity::BuyShares(params[1], params[2], params[3], params[4]))
+        } else if command == PLACE_ORDER {
+            enforce(params.len() == 5, "place order needs 5 params");
+            Command::Activity(Activity::PlaceOrder(params[1], params[2], params[3], params[4]))
+        } else if command == CANCEL_ORDER {
+            enforce(params.len() == 3, "cancel order needs 3 params");
+            Command::Activity(Activity::CancelOrder(params[1], params[2]))
+        } else if command == ADD_LIQUIDITY {
+            enforce(params.len() == 3, "add liquidity needs 3 params");
+            Command::Activity(Activity::AddLiquidity(params[1], params[2]))
+        } else if command == REMOVE_LIQUIDITY {
+            enforce(params.len() == 3, "remove liquidity needs 3 params");
+            Command::Activity(Activity::RemoveLiquidity(params[1], params[2]))
+        } else if command == SET_RESOLVERS {
+            enforce(params.len() >= 4, "set resolvers needs at least 4 params");
+            // Threshold, then the member count and each member's 4-word pubkey
+            let member_count = params[3] as usize;
+            enforce(member_count <= MAX_RESOLVERS, "too many resolvers");
+            enforce(params.len() == 4 + member_count * 4, "missing resolver pubkeys");
+            let members = params[4..].chunks(4)
+                .map(|pkey| Player::pkey_to_pid(&[pkey[0], pkey[1], pkey[2], pkey[3]]))
+                .collect();
+            Command::Activity(Activity::SetResolvers(params[1], params[2], members))
+        } else if command == VOTE_RESOLUTION {
+            enforce(params.len() == 3, "vote resolution needs 3 params");
+            Command::Activity(Activity::VoteResolution(params[1], params[2]))
+        } else if command == PROPOSE_OUTCOME {
+            enforce(params.len() == 3, "propose outcome needs 3 params");
+            Command::Activity(Activity::ProposeOutcome(params[1], params[2]))
+        } else if command == SETTLE_PROPOSAL {
+            enforce(params.len() == 2, "settle proposal needs 2 params");
+            Command::Activity(Activity::SettleProposal(params[1]))
+        } else if command == PAUSE {
+            // market_id (ALL_MARKETS for every market), then whether claims halt too
+            enforce(params.len() == 3, "pause needs 3 params");
+            Command::Activity(Activity::Pause(params[1], params[2] != 0))
+        } else if command == UNPAUSE {
+            enforce(params.len() == 2, "unpause needs 2 params");
+            Command::Activity(Activity::Unpause(params[1]))
+        } else if command == SET_CIRCUIT_BREAKER {
+            // market_id, largest price move in basis points, then the window and cool-down lengths
+            enforce(params.len() == 5, "set circuit breaker needs 5 params");
+            Command::Activity(Activity::SetCircuitBreaker(params[1], params[2], params[3], params[4]))
+        } else if command == MINT_SET {
+            enforce(params.len() == 3, "mint set needs 3 params");
+            Command::Activity(Activity::MintSet(params[1], params[2]))
+        } else if command == REDEEM_SET {
+            enforce(params.len() == 3, "redeem set needs 3 params");
+            Command::Activity(Activity::RedeemSet(params[1], params[2]))
+        } else if command == TRANSFER_SHARES {
+            // market_id, recipient player id (2 words), outcome index, shares
+            enforce(params.len() == 6, "transfer shares needs 6 params");
+            Command::Activity(Activity::TransferShares(params[1], [params[2], params[3]], params[4], params[5]))
+        } else if command == SET_FEE_SCHEDULE {
+            // market_id, fee at a 50% price, fee at 0% or 100%, then the pre-close ramp length and size
+            enforce(params.len() == 6, "set fee schedule needs 6 params");
+            Command::Activity(Activity::SetFeeSchedule(params[1], params[2], params[3], params[4], params[5]))
+        } else if command == SET_FEE_SPLIT {
+            // market_id, creator player id (2 words), then the platform, creator, LP and referrer weights
+            enforce(params.len() == 8, "set fee split needs 8 params");
+            let weights = [params[4], params[5], params[6], params[7]];
+            Command::Activity(Activity::SetFeeSplit(params[1], [params[2], params[3]], weights))
+        } else if command == WITHDRAW_CREATOR_FEES {
+            enforce(params.len() == 2, "withdraw creator fees needs 2 params");
+            Command::Activity(Activity::WithdrawCreatorFees(params[1]))
+        } else if command == SET_REFERRER {
+            enforce(params.len() == 3, "set referrer needs 3 params");
+            Command::Activity(Activity::SetReferrer([params[1], params[2]]))
+        } else if command == WITHDRAW_REFERRAL_FEES {
+            enforce(params.len() == 1, "withdraw referral fees needs 1 param");
+            Command::Activity(Activity::WithdrawReferralFees)
+        } else if command == DISPUTE {
+            enforce(params.len() == 2, "dispute needs 2 params");
+            Command::Activity(Activity::Dispute(params[1]))
+        } else if command == CANCEL_MARKET {
+            enforce(params.len() == 2, "cancel market needs 2 params");
+            Command::Activity(Activity::CancelMarket(params[1]))
         } else if command == INSTALL_PLAYER {
             Command::InstallPlayer
         } else {
@@ -220,7 +538,10 @@ impl Transaction {
 
     pub fn tick(&self) {
         GLOBAL_STATE.0.borrow_mut().counter += 1;
-        GLOBAL_STATE.0.borrow().emit_market_event();
+        let counter = GLOBAL_STATE.0.borrow().counter;
+        GlobalState::advance_live_markets(counter);
+        crate::circuit_breaker::resume_halted_markets(counter);
+        crate::order::match_limit_orders(counter);
     }
 
     pub fn inc_tx_number(&self) {
@@ -248,12 +569,32 @@ impl Transaction {
                 .map_or_else(|e| e, |_| 0),
             crate::command::Command::Withdraw(cmd) => cmd.handle(&pid, self.nonce, rand, counter)
                 .map_or_else(|e| e, |_| 0),
+            crate::command::Command::Transfer(cmd) => cmd.handle(&pid, self.nonce, rand, counter)
+                .map_or_else(|e| e, |_| 0),
             crate::command::Command::Activity(cmd) => {
                 // Check admin permissions for resolve and withdraw fees commands
-                if let Activity::Resolve(_) = cmd {
+                if let Activity::Resolve(_, _) = cmd {
+                    unsafe { require(*pkey == *ADMIN_PUBKEY) };
+                }
+                if let Activity::WithdrawFees(_) = cmd {
+                    unsafe { require(*pkey == *ADMIN_PUBKEY) };
+                }
+                if let Activity::CreateMarket(..) = cmd {
+                    unsafe { require(*pkey == *ADMIN_PUBKEY) };
+                }
+                if let Activity::CancelMarket(_) = cmd {
+                    unsafe { require(*pkey == *ADMIN_PUBKEY) };
+                }
+                if let Activity::SetResolvers(..) = cmd {
+                    unsafe { require(*pkey == *ADMIN_PUBKEY) };
+                }
+                if let Activity::Pause(..) | Activity::Unpause(_) = cmd {
+                    unsafe { require(*pkey == *ADMIN_PUBKEY) };
+                }
+                if let Activity::SetFeeSchedule(..) | Activity::SetFeeSplit(..) = cmd {
                     unsafe { require(*pkey == *ADMIN_PUBKEY) };
                 }
-                if let Activity::WithdrawFees = cmd {
+                if let Activity::SetCircuitBreaker(..) = cmd {
                     unsafe { require(*pkey == *ADMIN_PUBKEY) };
                 }
                 cmd.handle(&pid, self.nonce, rand, counter)
@@ -14,11 +14,76 @@ const SELL = 5;
 const RESOLVE = 6;
 const CLAIM = 7;
 const WITHDRAW_FEES = 8;
+const CREATE_MARKET = 9;
+const BUY_SHARES = 10;
+const PLACE_ORDER = 11;
+const CANCEL_ORDER = 12;
+const ADD_LIQUIDITY = 13;
+const REMOVE_LIQUIDITY = 14;
+const CANCEL_MARKET = 15;
+const CREATE_SCALAR_MARKET = 16;
+const DISPUTE = 17;
+const SET_RESOLVERS = 18;
+const VOTE_RESOLUTION = 19;
+const PROPOSE_OUTCOME = 20;
+const SETTLE_PROPOSAL = 21;
+const PAUSE = 22;
+const UNPAUSE = 23;
+const SET_CIRCUIT_BREAKER = 24;
+const MINT_SET = 25;
+const REDEEM_SET = 26;
+const TRANSFER_SHARES = 27;
+const TRANSFER = 28;
+const SET_FEE_SCHEDULE = 29;
+const SET_FEE_SPLIT = 30;
+const WITHDRAW_CREATOR_FEES = 31;
+const SET_REFERRER = 32;
+const WITHDRAW_REFERRAL_FEES = 33;
+
+// Pricing models accepted by CREATE_MARKET
+export const PRICING_CPMM = 0;
+export const PRICING_LMSR = 1;
+
+// Resolution outcome that voids a market and refunds traders' cost basis
+export const OUTCOME_INVALID = (1n << 64n) - 1n;
+
+// Market id that makes PAUSE / UNPAUSE apply to every market
+export const ALL_MARKETS = (1n << 64n) - 1n;
 
 // Fee constants - centralized to avoid duplication
 const PLATFORM_FEE_RATE = 100n; // 1%
 const FEE_BASIS_POINTS = 10000n;
 
+// Volume fee tiers, matching FEE_TIERS in config.rs: [cumulative traded volume, discount on the fee in bps]
+export const FEE_TIERS: [bigint, bigint][] = [[0n, 0n], [10000000n, 1000n], [100000000n, 2500n], [1000000000n, 5000n]];
+
+// Fee tier of a player's traded volume, and the rate after its discount
+export function feeTier(tradedVolume: bigint): number {
+    let tier = 0;
+    FEE_TIERS.forEach(([minVolume], i) => {
+        if (tradedVolume >= minVolume) tier = i;
+    });
+    return tier;
+}
+
+export function discountedRateBps(rateBps: bigint, tier: number): bigint {
+    return rateBps * (FEE_BASIS_POINTS - FEE_TIERS[tier][1]) / FEE_BASIS_POINTS;
+}
+
+// Pack a string as its byte length followed by little-endian 8-byte words (matches MarketText in market.rs)
+export function packString(s: string): bigint[] {
+    const bytes = new TextEncoder().encode(s);
+    const words: bigint[] = [BigInt(bytes.length)];
+    for (let i = 0; i < bytes.length; i += 8) {
+        let word = 0n;
+        for (let j = 0; j < 8 && i + j < bytes.length; j++) {
+            word |= BigInt(bytes[i + j]) << BigInt(8 * j);
+        }
+        words.push(word);
+    }
+    return words;
+}
+
 export class Player extends PlayerConvention {
     constructor(key: string, rpc: ZKWasmAppRpc) {
         super(key, rpc, BigInt(DEPOSIT), BigInt(WITHDRAW));
@@ -51,15 +116,15 @@ export class Player extends PlayerConvention {
         }
     }
 
-    async placeBet(betType: number, amount: bigint) {
+    async placeBet(marketId: bigint, betType: number, amount: bigint, minSharesOut: bigint = 0n) {
         let nonce = await this.getNonce();
-        let cmd = createCommand(nonce, BigInt(BET), [BigInt(betType), amount]);
+        let cmd = createCommand(nonce, BigInt(BET), [marketId, BigInt(betType), amount, minSharesOut]);
         return await this.sendTransactionWithCommand(cmd);
     }
 
-    async claimWinnings() {
+    async claimWinnings(marketId: bigint) {
         let nonce = await this.getNonce();
-        let cmd = createCommand(nonce, BigInt(CLAIM), []);
+        let cmd = createCommand(nonce, BigInt(CLAIM), [marketId]);
         return await this.sendTransactionWithCommand(cmd);
     }
 
@@ -75,21 +140,228 @@ export class Player extends PlayerConvention {
         return await this.sendTransactionWithCommand(cmd);
     }
 
-    async resolveMarket(outcome: boolean) {
+    // Move balance to another installed player inside the rollup
+    async transferFunds(amount: bigint, targetPid1: bigint, targetPid2: bigint) {
+        let nonce = await this.getNonce();
+        let cmd = createCommand(nonce, BigInt(TRANSFER), [targetPid1, targetPid2, amount]);
+        return await this.sendTransactionWithCommand(cmd);
+    }
+
+    async resolveMarket(marketId: bigint, outcome: bigint) {
+        let nonce = await this.getNonce();
+        let cmd = createCommand(nonce, BigInt(RESOLVE), [marketId, outcome]);
+        return await this.sendTransactionWithCommand(cmd);
+    }
+
+    // Appoint an M-of-N resolver committee; pubkeys are 4-word u64 arrays (LeHexBN.toU64Array())
+    async setResolvers(marketId: bigint, threshold: bigint, pubkeys: BigUint64Array[]) {
+        let nonce = await this.getNonce();
+        let members = pubkeys.flatMap(pkey => Array.from(pkey));
+        let cmd = createCommand(nonce, BigInt(SET_RESOLVERS), [marketId, threshold, BigInt(pubkeys.length), ...members]);
+        return await this.sendTransactionWithCommand(cmd);
+    }
+
+    async voteResolution(marketId: bigint, outcome: bigint) {
+        let nonce = await this.getNonce();
+        let cmd = createCommand(nonce, BigInt(VOTE_RESOLUTION), [marketId, outcome]);
+        return await this.sendTransactionWithCommand(cmd);
+    }
+
+    // Propose an outcome by posting the resolution bond from the player's balance
+    async proposeOutcome(marketId: bigint, outcome: bigint) {
+        let nonce = await this.getNonce();
+        let cmd = createCommand(nonce, BigInt(PROPOSE_OUTCOME), [marketId, outcome]);
+        return await this.sendTransactionWithCommand(cmd);
+    }
+
+    async settleProposal(marketId: bigint) {
+        let nonce = await this.getNonce();
+        let cmd = createCommand(nonce, BigInt(SETTLE_PROPOSAL), [marketId]);
+        return await this.sendTransactionWithCommand(cmd);
+    }
+
+    // Turn collateral into one share of every outcome per unit, without moving prices or paying fees
+    async mintSet(marketId: bigint, amount: bigint) {
+        let nonce = await this.getNonce();
+        let cmd = createCommand(nonce, BigInt(MINT_SET), [marketId, amount]);
+        return await this.sendTransactionWithCommand(cmd);
+    }
+
+    // Burn one share of every outcome per unit of collateral returned
+    async redeemSet(marketId: bigint, amount: bigint) {
+        let nonce = await this.getNonce();
+        let cmd = createCommand(nonce, BigInt(REDEEM_SET), [marketId, amount]);
+        return await this.sendTransactionWithCommand(cmd);
+    }
+
+    // Name the player who receives part of this player's trading fees; can only be set once
+    async setReferrer(referrerPid: [bigint, bigint]) {
+        let nonce = await this.getNonce();
+        let cmd = createCommand(nonce, BigInt(SET_REFERRER), [referrerPid[0], referrerPid[1]]);
+        return await this.sendTransactionWithCommand(cmd);
+    }
+
+    // Move shares of one outcome to another installed player, with a pro rata part of that outcome's cost basis
+    async transferShares(marketId: bigint, toPid: [bigint, bigint], outcome: bigint, shares: bigint) {
+        let nonce = await this.getNonce();
+        let cmd = createCommand(nonce, BigInt(TRANSFER_SHARES), [marketId, toPid[0], toPid[1], outcome, shares]);
+        return await this.sendTransactionWithCommand(cmd);
+    }
+
+    // Challenge the proposed outcome; matches the bond when a player proposed it, and posts
+    // RESOLUTION_BOND against an admin or committee outcome
+    async disputeResolution(marketId: bigint) {
+        let nonce = await this.getNonce();
+        let cmd = createCommand(nonce, BigInt(DISPUTE), [marketId]);
+        return await this.sendTransactionWithCommand(cmd);
+    }
+
+    async cancelMarket(marketId: bigint) {
+        let nonce = await this.getNonce();
+        let cmd = createCommand(nonce, BigInt(CANCEL_MARKET), [marketId]);
+        return await this.sendTransactionWithCommand(cmd);
+    }
+
+    // Halt trading on a market (or ALL_MARKETS), optionally halting claims too
+    async pause(marketId: bigint, haltClaims: boolean = false) {
+        let nonce = await this.getNonce();
+        let cmd = createCommand(nonce, BigInt(PAUSE), [marketId, haltClaims ? 1n : 0n]);
+        return await this.sendTransactionWithCommand(cmd);
+    }
+
+    async unpause(marketId: bigint) {
+        let nonce = await this.getNonce();
+        let cmd = createCommand(nonce, BigInt(UNPAUSE), [marketId]);
+        return await this.sendTransactionWithCommand(cmd);
+    }
+
+    // Set a market's circuit breaker: the largest move of any outcome price within a window
+    // of windowTicks, in basis points, and how long trading halts once it is exceeded
+    async setCircuitBreaker(marketId: bigint, moveBps: bigint, windowTicks: bigint, cooldownTicks: bigint) {
+        let nonce = await this.getNonce();
+        let cmd = createCommand(nonce, BigInt(SET_CIRCUIT_BREAKER), [marketId, moveBps, windowTicks, cooldownTicks]);
+        return await this.sendTransactionWithCommand(cmd);
+    }
+
+    // Set a market's fee curve in basis points: base at a 50% price, edge at 0% or 100%,
+    // plus up to rampBps added over the last rampTicks before endTime
+    async setFeeSchedule(marketId: bigint, baseBps: bigint, edgeBps: bigint, rampTicks: bigint = 0n, rampBps: bigint = 0n) {
+        let nonce = await this.getNonce();
+        let cmd = createCommand(nonce, BigInt(SET_FEE_SCHEDULE), [marketId, baseBps, edgeBps, rampTicks, rampBps]);
+        return await this.sendTransactionWithCommand(cmd);
+    }
+
+    // Share a market's future fees between platform, creator, LPs and referrers (weights add up to 10000)
+    async setFeeSplit(marketId: bigint, creatorPid: [bigint, bigint], platformBps: bigint, creatorBps: bigint, lpBps: bigint, referrerBps: bigint) {
+        let nonce = await this.getNonce();
+        let cmd = createCommand(nonce, BigInt(SET_FEE_SPLIT), [marketId, creatorPid[0], creatorPid[1], platformBps, creatorBps, lpBps, referrerBps]);
+        return await this.sendTransactionWithCommand(cmd);
+    }
+
+    // Withdraw the creator share of a market's fees, only for its creator
+    async withdrawCreatorFees(marketId: bigint) {
+        let nonce = await this.getNonce();
+        let cmd = createCommand(nonce, BigInt(WITHDRAW_CREATOR_FEES), [marketId]);
+        return await this.sendTransactionWithCommand(cmd);
+    }
+
+    // Withdraw the fee rebates accrued from referred players to the balance
+    async withdrawReferralFees() {
+        let nonce = await this.getNonce();
+        let cmd = createCommand(nonce, BigInt(WITHDRAW_REFERRAL_FEES), []);
+        return await this.sendTransactionWithCommand(cmd);
+    }
+
+    async withdrawFees(marketId: bigint) {
+        let nonce = await this.getNonce();
+        let cmd = createCommand(nonce, BigInt(WITHDRAW_FEES), [marketId]);
+        return await this.sendTransactionWithCommand(cmd);
+    }
+
+    async buyShares(marketId: bigint, betType: number, shares: bigint, maxCost: bigint) {
+        let nonce = await this.getNonce();
+        let cmd = createCommand(nonce, BigInt(BUY_SHARES), [marketId, BigInt(betType), shares, maxCost]);
+        return await this.sendTransactionWithCommand(cmd);
+    }
+
+    async placeOrder(marketId: bigint, outcome: number, limitPrice: bigint, amount: bigint) {
+        let nonce = await this.getNonce();
+        let cmd = createCommand(nonce, BigInt(PLACE_ORDER), [marketId, BigInt(outcome), limitPrice, amount]);
+        return await this.sendTransactionWithCommand(cmd);
+    }
+
+    async cancelOrder(marketId: bigint, orderId: bigint) {
+        let nonce = await this.getNonce();
+        let cmd = createCommand(nonce, BigInt(CANCEL_ORDER), [marketId, orderId]);
+        return await this.sendTransactionWithCommand(cmd);
+    }
+
+    async addLiquidity(marketId: bigint, amount: bigint) {
+        let nonce = await this.getNonce();
+        let cmd = createCommand(nonce, BigInt(ADD_LIQUIDITY), [marketId, amount]);
+        return await this.sendTransactionWithCommand(cmd);
+    }
+
+    // Redeemed at par while trading, settled with the outcome once final, rejected in between
+    async removeLiquidity(marketId: bigint, lpShares: bigint) {
         let nonce = await this.getNonce();
-        let cmd = createCommand(nonce, BigInt(RESOLVE), [outcome ? 1n : 0n]);
+        let cmd = createCommand(nonce, BigInt(REMOVE_LIQUIDITY), [marketId, lpShares]);
         return await this.sendTransactionWithCommand(cmd);
     }
 
-    async withdrawFees() {
+    async sellShares(marketId: bigint, sellType: number, shares: bigint, minPayout: bigint = 0n) {
         let nonce = await this.getNonce();
-        let cmd = createCommand(nonce, BigInt(WITHDRAW_FEES), []);
+        let cmd = createCommand(nonce, BigInt(SELL), [marketId, BigInt(sellType), shares, minPayout]);
         return await this.sendTransactionWithCommand(cmd);
     }
 
-    async sellShares(sellType: number, shares: bigint) {
+    async createMarket(
+        title: string,
+        description: string,
+        resolutionCriteria: string,
+        outcomes: string[],
+        startTime: bigint,
+        endTime: bigint,
+        resolutionTime: bigint,
+        liquidity: bigint[],
+        pricingModel: number = PRICING_CPMM,
+        lmsrB: bigint = 0n
+    ) {
         let nonce = await this.getNonce();
-        let cmd = createCommand(nonce, BigInt(SELL), [BigInt(sellType), shares]);
+        let text = [
+            ...packString(title),
+            ...packString(description),
+            ...packString(resolutionCriteria),
+            BigInt(outcomes.length),
+            ...outcomes.flatMap(packString),
+        ];
+        let cmd = createCommand(nonce, BigInt(CREATE_MARKET), [startTime, endTime, resolutionTime, BigInt(pricingModel), lmsrB, BigInt(liquidity.length), ...liquidity, ...text]);
+        return await this.sendTransactionWithCommand(cmd);
+    }
+
+    // Scalar market: SHORT and LONG split the prize pool linearly on the value passed to resolve
+    async createScalarMarket(
+        title: string,
+        description: string,
+        resolutionCriteria: string,
+        lowerBound: bigint,
+        upperBound: bigint,
+        startTime: bigint,
+        endTime: bigint,
+        resolutionTime: bigint,
+        liquidity: [bigint, bigint], // [SHORT, LONG]
+        pricingModel: number = PRICING_CPMM,
+        lmsrB: bigint = 0n
+    ) {
+        let nonce = await this.getNonce();
+        let text = [
+            ...packString(title),
+            ...packString(description),
+            ...packString(resolutionCriteria),
+            2n,
+            ...["Short", "Long"].flatMap(packString),
+        ];
+        let cmd = createCommand(nonce, BigInt(CREATE_SCALAR_MARKET), [startTime, endTime, resolutionTime, BigInt(pricingModel), lmsrB, lowerBound, upperBound, ...liquidity, ...text]);
         return await this.sendTransactionWithCommand(cmd);
     }
 
@@ -97,26 +369,69 @@ export class Player extends PlayerConvention {
 }
 
 
+export type MarketStatus = "scheduled" | "open" | "closed" | "proposed" | "disputed" | "finalized" | "cancelled";
+
+// Status codes carried by MARKET_STATUS events, in the order of MarketStatus
+export const MARKET_STATUSES: MarketStatus[] = ["scheduled", "open", "closed", "proposed", "disputed", "finalized", "cancelled"];
+
+// Basis points charged on a trade, see feeRateBps
+export interface FeeSchedule {
+    baseBps: string;
+    edgeBps: string;
+    rampTicks: string;
+    rampBps: string;
+}
+
 export interface MarketData {
     title: string;
     description: string;
+    resolutionCriteria: string;
+    outcomes: string[];
     startTime: string;
     endTime: string;
     resolutionTime: string;
-    yesLiquidity: string;
-    noLiquidity: string;
+    liquidity: string[]; // Pricing state per outcome index, [NO, YES] for binary markets
+    pricing: { type: "cpmm" } | { type: "lmsr", b: string };
     totalVolume: string;
-    resolved: boolean;
-    outcome: boolean | null;
+    status: MarketStatus; // Stored status; time-driven transitions are applied in the state snapshot
+    outcome: number | null; // Winning outcome index, or the settlement value of a scalar market
+    scalar: { lower: string, upper: string } | null; // Range of a scalar market, outcomes are [SHORT, LONG]
+    invalid: boolean; // Resolved as invalid or cancelled, claims refund cost basis
+    disputeEnd: string; // Tick at which the proposed outcome becomes final
     totalFeesCollected: string;
+    lpPool: string; // Complete sets held for liquidity providers, one per unit deposited
+    lpFees: string; // LP share of trading fees
+    totalLpShares: string;
+    pause: { trading: boolean, claims: boolean }; // Set by the admin; the global pause is in the state
+    breaker: {
+        limits: { moveBps: string, windowTicks: string, cooldownTicks: string };
+        windowStart: string;
+        referencePrices: string[]; // Per outcome, snapshotted on tick at the start of each window
+        haltedUntil: string;
+    }; // Circuit breaker, trading halted until haltedUntil
+    feeSchedule: FeeSchedule; // Fee curve of the market, a flat 1% unless set by the admin
+    feeSplit: { platformBps: string, creatorBps: string, lpBps: string, referrerBps: string }; // Weights of each fee's beneficiaries
+    creator: string[]; // Player id credited with the creator share
+    creatorFees: string; // Creator share of fees, withdrawn with withdrawCreatorFees
+    referrerFees: string; // Referrer share of the fee being settled, zero between trades
     yesPrice: string;
     noPrice: string;
 }
 
 export interface PlayerData {
     balance: string;
-    yesShares: string;
-    noShares: string;
+    lockedBalance: string; // Held by resting limit orders
+    tradedVolume: string; // Cumulative bet, buy and sell amounts
+    feesPaid: string;
+    referrer: string[] | null; // Receives part of this player's trading fees
+    referralCount: string; // Players who named this player as their referrer
+    referralEarnings: string; // Fee rebates accrued from referred players, in total
+    referralFees: string; // Accrued fee rebates not yet withdrawn with withdrawReferralFees
+    shares: string[]; // Shares held per outcome index
+    lpShares: string; // Liquidity provider shares
+    costBasis: string; // Paid in minus sale proceeds, refunded if the market is invalid
+    setShares: string; // Minted complete sets, only redeemable as sets
+    outcomeCostBasis: string[]; // Part of costBasis paid for each outcome's free shares
     claimed: boolean;
 }
 
@@ -197,12 +512,30 @@ export class PredictionMarketAPI {
         return result.data;
     }
 
+    // Fee rate of a trade at the pre-trade price (PRICE_PRECISION = 1000000), timeToClose ticks before endTime
+    feeRateBps(schedule: FeeSchedule, price: bigint, timeToClose: bigint): bigint {
+        const half = 500000n;
+        const base = BigInt(schedule.baseBps);
+        const edge = BigInt(schedule.edgeBps);
+        let distance = price > half ? price - half : half - price;
+        if (distance > half) distance = half;
+        const priceRate = edge >= base
+            ? base + (edge - base) * distance / half
+            : base - (base - edge) * distance / half;
+
+        const rampTicks = BigInt(schedule.rampTicks);
+        const rampRate = timeToClose < rampTicks
+            ? BigInt(schedule.rampBps) * (rampTicks - timeToClose) / rampTicks
+            : 0n;
+        return priceRate + rampRate;
+    }
+
     // Unified shares calculation function (betType: 1=YES, 0=NO)
-    calculateShares(betType: number, amount: number, yesLiquidity: bigint, noLiquidity: bigint): bigint {
+    calculateShares(betType: number, amount: number, yesLiquidity: bigint, noLiquidity: bigint, feeBps: bigint = PLATFORM_FEE_RATE): bigint {
         if (amount <= 0) return 0n;
 
         // 向上取整计算费用：(amount * rate + basis - 1) / basis
-        const fee = (BigInt(amount) * PLATFORM_FEE_RATE + FEE_BASIS_POINTS - 1n) / FEE_BASIS_POINTS;
+        const fee = (BigInt(amount) * feeBps + FEE_BASIS_POINTS - 1n) / FEE_BASIS_POINTS;
         const netAmount = BigInt(amount) - fee;
         const k = yesLiquidity * noLiquidity;
         const isYesBet = betType === 1;
@@ -221,7 +554,7 @@ export class PredictionMarketAPI {
 
 
     // Calculate sell details (net payout and fee) - unified function
-    calculateSellDetails(sellType: number, shares: number, yesLiquidity: bigint, noLiquidity: bigint): { netPayout: bigint, fee: bigint } {
+    calculateSellDetails(sellType: number, shares: number, yesLiquidity: bigint, noLiquidity: bigint, feeBps: bigint = PLATFORM_FEE_RATE): { netPayout: bigint, fee: bigint } {
         if (shares <= 0) return { netPayout: 0n, fee: 0n };
 
         const k = yesLiquidity * noLiquidity;
@@ -248,7 +581,7 @@ export class PredictionMarketAPI {
         }
 
         // 向上取整计算费用：(amount * rate + basis - 1) / basis
-        const fee = (grossAmount * PLATFORM_FEE_RATE + FEE_BASIS_POINTS - 1n) / FEE_BASIS_POINTS;
+        const fee = (grossAmount * feeBps + FEE_BASIS_POINTS - 1n) / FEE_BASIS_POINTS;
         const netPayout = grossAmount - fee;
         
         return { netPayout, fee };
@@ -355,24 +688,129 @@ export class PredictionMarketAPI {
 }
 
 // Transaction building utilities
-export function buildBetTransaction(nonce: number, betType: number, amount: bigint): bigint[] {
+export function buildBetTransaction(nonce: number, marketId: bigint, betType: number, amount: bigint, minSharesOut: bigint = 0n): bigint[] {
     const commandWithNonce = BigInt(BET) | (BigInt(nonce) << 16n);
-    return [commandWithNonce, BigInt(betType), amount, 0n, 0n];
+    return [commandWithNonce, marketId, BigInt(betType), amount, minSharesOut];
 }
 
-export function buildSellTransaction(nonce: number, sellType: number, shares: bigint): bigint[] {
+export function buildSellTransaction(nonce: number, marketId: bigint, sellType: number, shares: bigint, minPayout: bigint = 0n): bigint[] {
     const commandWithNonce = BigInt(SELL) | (BigInt(nonce) << 16n);
-    return [commandWithNonce, BigInt(sellType), shares, 0n, 0n];
+    return [commandWithNonce, marketId, BigInt(sellType), shares, minPayout];
+}
+
+export function buildBuySharesTransaction(nonce: number, marketId: bigint, betType: number, shares: bigint, maxCost: bigint): bigint[] {
+    const commandWithNonce = BigInt(BUY_SHARES) | (BigInt(nonce) << 16n);
+    return [commandWithNonce, marketId, BigInt(betType), shares, maxCost];
+}
+
+export function buildPlaceOrderTransaction(nonce: number, marketId: bigint, outcome: number, limitPrice: bigint, amount: bigint): bigint[] {
+    const commandWithNonce = BigInt(PLACE_ORDER) | (BigInt(nonce) << 16n);
+    return [commandWithNonce, marketId, BigInt(outcome), limitPrice, amount];
+}
+
+export function buildCancelOrderTransaction(nonce: number, marketId: bigint, orderId: bigint): bigint[] {
+    const commandWithNonce = BigInt(CANCEL_ORDER) | (BigInt(nonce) << 16n);
+    return [commandWithNonce, marketId, orderId];
+}
+
+export function buildAddLiquidityTransaction(nonce: number, marketId: bigint, amount: bigint): bigint[] {
+    const commandWithNonce = BigInt(ADD_LIQUIDITY) | (BigInt(nonce) << 16n);
+    return [commandWithNonce, marketId, amount];
 }
 
-export function buildResolveTransaction(nonce: number, outcome: boolean): bigint[] {
+export function buildRemoveLiquidityTransaction(nonce: number, marketId: bigint, lpShares: bigint): bigint[] {
+    const commandWithNonce = BigInt(REMOVE_LIQUIDITY) | (BigInt(nonce) << 16n);
+    return [commandWithNonce, marketId, lpShares];
+}
+
+export function buildVoteResolutionTransaction(nonce: number, marketId: bigint, outcome: bigint): bigint[] {
+    const commandWithNonce = BigInt(VOTE_RESOLUTION) | (BigInt(nonce) << 16n);
+    return [commandWithNonce, marketId, outcome];
+}
+
+export function buildProposeOutcomeTransaction(nonce: number, marketId: bigint, outcome: bigint): bigint[] {
+    const commandWithNonce = BigInt(PROPOSE_OUTCOME) | (BigInt(nonce) << 16n);
+    return [commandWithNonce, marketId, outcome];
+}
+
+export function buildSettleProposalTransaction(nonce: number, marketId: bigint): bigint[] {
+    const commandWithNonce = BigInt(SETTLE_PROPOSAL) | (BigInt(nonce) << 16n);
+    return [commandWithNonce, marketId];
+}
+
+export function buildDisputeTransaction(nonce: number, marketId: bigint): bigint[] {
+    const commandWithNonce = BigInt(DISPUTE) | (BigInt(nonce) << 16n);
+    return [commandWithNonce, marketId];
+}
+
+export function buildMintSetTransaction(nonce: number, marketId: bigint, amount: bigint): bigint[] {
+    const commandWithNonce = BigInt(MINT_SET) | (BigInt(nonce) << 16n);
+    return [commandWithNonce, marketId, amount];
+}
+
+export function buildRedeemSetTransaction(nonce: number, marketId: bigint, amount: bigint): bigint[] {
+    const commandWithNonce = BigInt(REDEEM_SET) | (BigInt(nonce) << 16n);
+    return [commandWithNonce, marketId, amount];
+}
+
+export function buildTransferSharesTransaction(nonce: number, marketId: bigint, toPid: [bigint, bigint], outcome: bigint, shares: bigint): bigint[] {
+    const commandWithNonce = BigInt(TRANSFER_SHARES) | (BigInt(nonce) << 16n);
+    return [commandWithNonce, marketId, toPid[0], toPid[1], outcome, shares];
+}
+
+export function buildPauseTransaction(nonce: number, marketId: bigint, haltClaims: boolean): bigint[] {
+    const commandWithNonce = BigInt(PAUSE) | (BigInt(nonce) << 16n);
+    return [commandWithNonce, marketId, haltClaims ? 1n : 0n];
+}
+
+export function buildUnpauseTransaction(nonce: number, marketId: bigint): bigint[] {
+    const commandWithNonce = BigInt(UNPAUSE) | (BigInt(nonce) << 16n);
+    return [commandWithNonce, marketId];
+}
+
+export function buildSetCircuitBreakerTransaction(nonce: number, marketId: bigint, moveBps: bigint, windowTicks: bigint, cooldownTicks: bigint): bigint[] {
+    const commandWithNonce = BigInt(SET_CIRCUIT_BREAKER) | (BigInt(nonce) << 16n);
+    return [commandWithNonce, marketId, moveBps, windowTicks, cooldownTicks];
+}
+
+export function buildSetFeeScheduleTransaction(nonce: number, marketId: bigint, baseBps: bigint, edgeBps: bigint, rampTicks: bigint, rampBps: bigint): bigint[] {
+    const commandWithNonce = BigInt(SET_FEE_SCHEDULE) | (BigInt(nonce) << 16n);
+    return [commandWithNonce, marketId, baseBps, edgeBps, rampTicks, rampBps];
+}
+
+export function buildSetFeeSplitTransaction(nonce: number, marketId: bigint, creatorPid: [bigint, bigint], platformBps: bigint, creatorBps: bigint, lpBps: bigint, referrerBps: bigint): bigint[] {
+    const commandWithNonce = BigInt(SET_FEE_SPLIT) | (BigInt(nonce) << 16n);
+    return [commandWithNonce, marketId, creatorPid[0], creatorPid[1], platformBps, creatorBps, lpBps, referrerBps];
+}
+
+export function buildWithdrawCreatorFeesTransaction(nonce: number, marketId: bigint): bigint[] {
+    const commandWithNonce = BigInt(WITHDRAW_CREATOR_FEES) | (BigInt(nonce) << 16n);
+    return [commandWithNonce, marketId];
+}
+
+export function buildWithdrawReferralFeesTransaction(nonce: number): bigint[] {
+    const commandWithNonce = BigInt(WITHDRAW_REFERRAL_FEES) | (BigInt(nonce) << 16n);
+    return [commandWithNonce];
+}
+
+export function buildSetReferrerTransaction(nonce: number, referrerPid: [bigint, bigint]): bigint[] {
+    const commandWithNonce = BigInt(SET_REFERRER) | (BigInt(nonce) << 16n);
+    return [commandWithNonce, referrerPid[0], referrerPid[1]];
+}
+
+export function buildCancelMarketTransaction(nonce: number, marketId: bigint): bigint[] {
+    const commandWithNonce = BigInt(CANCEL_MARKET) | (BigInt(nonce) << 16n);
+    return [commandWithNonce, marketId];
+}
+
+export function buildResolveTransaction(nonce: number, marketId: bigint, outcome: bigint): bigint[] {
     const commandWithNonce = BigInt(RESOLVE) | (BigInt(nonce) << 16n);
-    return [commandWithNonce, outcome ? 1n : 0n, 0n, 0n, 0n];
+    return [commandWithNonce, marketId, outcome, 0n, 0n];
 }
 
-export function buildClaimTransaction(nonce: number): bigint[] {
+export function buildClaimTransaction(nonce: number, marketId: bigint): bigint[] {
     const commandWithNonce = BigInt(CLAIM) | (BigInt(nonce) << 16n);
-    return [commandWithNonce, 0n, 0n, 0n, 0n];
+    return [commandWithNonce, marketId, 0n, 0n, 0n];
 }
 
 export function buildWithdrawTransaction(
@@ -395,6 +833,16 @@ export function buildDepositTransaction(
     return [commandWithNonce, targetPid1, targetPid2, 0n, amount];
 }
 
+export function buildTransferTransaction(
+    nonce: number,
+    targetPid1: bigint,
+    targetPid2: bigint,
+    amount: bigint
+): bigint[] {
+    const commandWithNonce = BigInt(TRANSFER) | (BigInt(nonce) << 16n);
+    return [commandWithNonce, targetPid1, targetPid2, amount];
+}
+
 export function buildInstallPlayerTransaction(nonce: number): bigint[] {
     const commandWithNonce = BigInt(INSTALL_PLAYER) | (BigInt(nonce) << 16n);
     return [commandWithNonce, 0n, 0n, 0n, 0n];
@@ -419,9 +867,9 @@ export async function exampleUsage() {
         console.log("Market data:", marketData);
 
         // Calculate prices and expected values
-        if (marketData.yesLiquidity && marketData.noLiquidity) {
-            const yesLiquidity = BigInt(marketData.yesLiquidity);
-            const noLiquidity = BigInt(marketData.noLiquidity);
+        if (marketData.liquidity && marketData.liquidity.length == 2) {
+            const noLiquidity = BigInt(marketData.liquidity[0]);
+            const yesLiquidity = BigInt(marketData.liquidity[1]);
             
             // Current market prices
             const prices = api.calculatePrices(yesLiquidity, noLiquidity);
@@ -447,7 +895,7 @@ export async function exampleUsage() {
 
             // Place a bet
             console.log("Placing YES bet...");
-            await player.placeBet(1, 1000n); // YES bet for 1000 units
+            await player.placeBet(0n, 1, 1000n); // YES bet for 1000 units
 
             // Get updated market stats
             const stats = await api.getStats();
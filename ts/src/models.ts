@@ -18,38 +18,46 @@ export function docToJSON(doc: mongoose.Document) {
 
 
 interface MarketInfo {
+    marketId: bigint;
     counter: bigint;
-    yesLiquidity: bigint;
-    noLiquidity: bigint;
+    liquidity: bigint[]; // Reserve per outcome index
 }
 
 // Market Schema
 const marketSchema = new mongoose.Schema<MarketInfo>({
-    counter: { type: BigInt, required: true, unique: true},
-    yesLiquidity: { type: BigInt, required: true },
-    noLiquidity: { type: BigInt, required: true },
+    marketId: { type: BigInt, required: true },
+    counter: { type: BigInt, required: true },
+    liquidity: { type: [BigInt], required: true },
 });
 
 marketSchema.pre('init', ObjectEvent.uint64FetchPlugin);
 
+marketSchema.index({ marketId: 1, counter: 1 }, { unique: true });
+
 
 export interface Bet {
     index: bigint;
+    marketId: bigint;
     pid: bigint[],
     betType: number,
     amount: bigint,
     shares: bigint,
     counter: bigint,
+    fee: bigint,
+    feeTier: number, // Volume tier the fee was charged at, index into FEE_TIERS
 }
 
 // Bet Schema
 const betSchema = new mongoose.Schema<Bet>({
     index: { type: BigInt, required: true, unique: true},
+    marketId: { type: BigInt, required: true },
     pid: { type: [BigInt], required: true },
-    betType: { type: Number, required: true }, // 0 = NO, 1 = YES
+    betType: { type: Number, required: true }, // Outcome index, 0 = NO, 1 = YES for binary markets
     amount: { type: BigInt, required: true },
     shares: { type: BigInt, required: true },
-    counter: { type: BigInt, required: true}
+    counter: { type: BigInt, required: true},
+    fee: { type: BigInt, required: true },
+    feeTier: { type: Number, required: true },
 });
 
 betSchema.pre('init', ObjectEvent.uint64FetchPlugin);
@@ -57,31 +65,199 @@ betSchema.pre('init', ObjectEvent.uint64FetchPlugin);
 betSchema.index({ pid1: 1, pid2: 1 });
 betSchema.index({ timestamp: -1 });
 
+export interface Order {
+    orderId: bigint;
+    marketId: bigint;
+    pid: bigint[],
+    outcome: number,
+    limitPrice: bigint,
+    amount: bigint,
+    status: number, // 0 = placed, 1 = filled, 2 = cancelled
+    shares: bigint,
+    counter: bigint,
+    feeTier: number, // Volume tier of a fill, 0 otherwise
+}
+
+// Order Schema, one document per order updated as its status changes
+const orderSchema = new mongoose.Schema<Order>({
+    orderId: { type: BigInt, required: true, unique: true },
+    marketId: { type: BigInt, required: true },
+    pid: { type: [BigInt], required: true },
+    outcome: { type: Number, required: true },
+    limitPrice: { type: BigInt, required: true },
+    amount: { type: BigInt, required: true },
+    status: { type: Number, required: true },
+    shares: { type: BigInt, required: true },
+    counter: { type: BigInt, required: true },
+    feeTier: { type: Number, required: true },
+});
+
+orderSchema.pre('init', ObjectEvent.uint64FetchPlugin);
+
+orderSchema.index({ marketId: 1, status: 1 });
+
+export interface Liquidity {
+    marketId: bigint;
+    pid: bigint[],
+    action: number, // 0 = added, 1 = removed
+    amount: bigint, // collateral deposited, or collateral plus fees paid out
+    lpShares: bigint,
+    counter: bigint,
+}
+
+// Liquidity Schema, one document per LP deposit or withdrawal
+const liquiditySchema = new mongoose.Schema<Liquidity>({
+    marketId: { type: BigInt, required: true },
+    pid: { type: [BigInt], required: true },
+    action: { type: Number, required: true },
+    amount: { type: BigInt, required: true },
+    lpShares: { type: BigInt, required: true },
+    counter: { type: BigInt, required: true },
+});
+
+liquiditySchema.pre('init', ObjectEvent.uint64FetchPlugin);
+
+liquiditySchema.index({ marketId: 1 });
+
+export interface CompleteSet {
+    marketId: bigint;
+    pid: bigint[],
+    action: number, // 0 = minted, 1 = redeemed
+    amount: bigint, // collateral in or out, equal to the sets minted or burned
+    counter: bigint,
+}
+
+// Complete set Schema, one document per mint or redemption
+const completeSetSchema = new mongoose.Schema<CompleteSet>({
+    marketId: { type: BigInt, required: true },
+    pid: { type: [BigInt], required: true },
+    action: { type: Number, required: true },
+    amount: { type: BigInt, required: true },
+    counter: { type: BigInt, required: true },
+});
+
+completeSetSchema.pre('init', ObjectEvent.uint64FetchPlugin);
+
+completeSetSchema.index({ marketId: 1 });
+
+export interface ShareTransfer {
+    marketId: bigint;
+    from: bigint[],
+    to: bigint[],
+    outcome: bigint,
+    shares: bigint,
+    counter: bigint,
+}
+
+// Share transfer Schema, one document per transfer between players
+const shareTransferSchema = new mongoose.Schema<ShareTransfer>({
+    marketId: { type: BigInt, required: true },
+    from: { type: [BigInt], required: true },
+    to: { type: [BigInt], required: true },
+    outcome: { type: BigInt, required: true },
+    shares: { type: BigInt, required: true },
+    counter: { type: BigInt, required: true },
+});
+
+shareTransferSchema.pre('init', ObjectEvent.uint64FetchPlugin);
+
+shareTransferSchema.index({ marketId: 1 });
+
+export interface ResolutionVote {
+    marketId: bigint;
+    pid: bigint[],
+    outcome: bigint, // outcome index, scalar value, or 2^64-1 for invalid
+    votes: bigint, // members currently backing this outcome
+    threshold: bigint,
+    counter: bigint,
+}
+
+// Resolution vote Schema, one document per committee vote
+const resolutionVoteSchema = new mongoose.Schema<ResolutionVote>({
+    marketId: { type: BigInt, required: true },
+    pid: { type: [BigInt], required: true },
+    outcome: { type: BigInt, required: true },
+    votes: { type: BigInt, required: true },
+    threshold: { type: BigInt, required: true },
+    counter: { type: BigInt, required: true },
+});
+
+resolutionVoteSchema.pre('init', ObjectEvent.uint64FetchPlugin);
+
+resolutionVoteSchema.index({ marketId: 1 });
+
+export interface Proposal {
+    marketId: bigint;
+    proposer: bigint[],
+    challenger: bigint[], // [0, 0] while unchallenged
+    outcome: bigint,
+    bond: bigint,
+    status: number, // 0 = proposed, 1 = challenged, 2 = settled
+    counter: bigint,
+}
+
+export interface MarketStatusInfo {
+    marketId: bigint;
+    status: number, // index into MARKET_STATUSES
+    counter: bigint,
+}
+
+// Market status Schema, one document per market holding its latest transition
+const marketStatusSchema = new mongoose.Schema<MarketStatusInfo>({
+    marketId: { type: BigInt, required: true, unique: true },
+    status: { type: Number, required: true },
+    counter: { type: BigInt, required: true },
+});
+
+marketStatusSchema.pre('init', ObjectEvent.uint64FetchPlugin);
+
+// Proposal Schema, one document per market updated as the proposal progresses
+const proposalSchema = new mongoose.Schema<Proposal>({
+    marketId: { type: BigInt, required: true, unique: true },
+    proposer: { type: [BigInt], required: true },
+    challenger: { type: [BigInt], required: true },
+    outcome: { type: BigInt, required: true },
+    bond: { type: BigInt, required: true },
+    status: { type: Number, required: true },
+    counter: { type: BigInt, required: true },
+});
+
+proposalSchema.pre('init', ObjectEvent.uint64FetchPlugin);
+
 export const MarketModel = mongoose.model('Market', marketSchema);
 export const BetModel = mongoose.model('Bet', betSchema);
+export const OrderModel = mongoose.model('Order', orderSchema);
+export const LiquidityModel = mongoose.model('Liquidity', liquiditySchema);
+export const CompleteSetModel = mongoose.model('CompleteSet', completeSetSchema);
+export const ShareTransferModel = mongoose.model('ShareTransfer', shareTransferSchema);
+export const ResolutionVoteModel = mongoose.model('ResolutionVote', resolutionVoteSchema);
+export const ProposalModel = mongoose.model('Proposal', proposalSchema);
+export const MarketStatusModel = mongoose.model('MarketStatus', marketStatusSchema);
 
 // Event handling classes
 export class MarketEvent {
+    marketId: bigint;
     index: bigint;
     data: bigint[];
 
     constructor(
-        index: bigint, data: bigint[]
+        marketId: bigint, index: bigint, data: bigint[]
     ) {
+        this.marketId = marketId;
         this.index = index;
         this.data = data;
     }
 
     static fromEvent(data: BigUint64Array): MarketEvent {
-        return new MarketEvent(data[0],  Array.from(data.slice(1)));
+        return new MarketEvent(data[0], data[1], Array.from(data.slice(2)));
     }
 
 
     toObject() {
         return {
+            marketId: this.marketId,
             counter: this.index,
-            yesLiquidity: this.data[0],
-            noLiquidity: this.data[1],
+            liquidity: this.data.slice(1, 1 + Number(this.data[0])),
         };
     }
 }
@@ -103,15 +279,177 @@ export class BetEvent {
     toObject(): Bet {
         return {
             index: this.index,
-            pid: [this.data[0], this.data[1]], // pid2
-            betType: Number(this.data[2]), // betType
-            amount: this.data[3],
-            shares: this.data[4],
+            marketId: this.data[0],
+            pid: [this.data[1], this.data[2]], // pid2
+            betType: Number(this.data[3]), // betType
+            amount: this.data[4],
+            shares: this.data[5],
+            counter: this.data[6],
+            fee: this.data[7],
+            feeTier: Number(this.data[8]),
+        };
+    }
+}
+
+export class OrderEvent {
+    orderId: bigint;
+    data: bigint[];
+    constructor(
+        orderId: bigint, data: bigint[]
+    ) {
+        this.orderId = orderId;
+        this.data = data;
+    }
+
+    static fromEvent(data: BigUint64Array): OrderEvent {
+        return new OrderEvent(data[0], Array.from(data.slice(1)));
+    }
+
+    toObject(): Order {
+        return {
+            orderId: this.orderId,
+            marketId: this.data[0],
+            pid: [this.data[1], this.data[2]],
+            outcome: Number(this.data[3]),
+            limitPrice: this.data[4],
+            amount: this.data[5],
+            status: Number(this.data[6]),
+            shares: this.data[7],
+            counter: this.data[8],
+            feeTier: Number(this.data[9]),
+        };
+    }
+}
+
+export class CompleteSetEvent {
+    data: bigint[];
+    constructor(data: bigint[]) {
+        this.data = data;
+    }
+
+    static fromEvent(data: BigUint64Array): CompleteSetEvent {
+        return new CompleteSetEvent(Array.from(data));
+    }
+
+    toObject(): CompleteSet {
+        return {
+            marketId: this.data[0],
+            pid: [this.data[1], this.data[2]],
+            action: Number(this.data[3]),
+            amount: this.data[4],
             counter: this.data[5],
         };
     }
 }
 
+export class ShareTransferEvent {
+    data: bigint[];
+    constructor(data: bigint[]) {
+        this.data = data;
+    }
+
+    static fromEvent(data: BigUint64Array): ShareTransferEvent {
+        return new ShareTransferEvent(Array.from(data));
+    }
+
+    toObject(): ShareTransfer {
+        return {
+            marketId: this.data[0],
+            from: [this.data[1], this.data[2]],
+            to: [this.data[3], this.data[4]],
+            outcome: this.data[5],
+            shares: this.data[6],
+            counter: this.data[7],
+        };
+    }
+}
+
+export class LiquidityEvent {
+    data: bigint[];
+    constructor(data: bigint[]) {
+        this.data = data;
+    }
+
+    static fromEvent(data: BigUint64Array): LiquidityEvent {
+        return new LiquidityEvent(Array.from(data));
+    }
+
+    toObject(): Liquidity {
+        return {
+            marketId: this.data[0],
+            pid: [this.data[1], this.data[2]],
+            action: Number(this.data[3]),
+            amount: this.data[4],
+            lpShares: this.data[5],
+            counter: this.data[6],
+        };
+    }
+}
+
+export class ResolutionVoteEvent {
+    data: bigint[];
+    constructor(data: bigint[]) {
+        this.data = data;
+    }
+
+    static fromEvent(data: BigUint64Array): ResolutionVoteEvent {
+        return new ResolutionVoteEvent(Array.from(data));
+    }
+
+    toObject(): ResolutionVote {
+        return {
+            marketId: this.data[0],
+            pid: [this.data[1], this.data[2]],
+            outcome: this.data[3],
+            votes: this.data[4],
+            threshold: this.data[5],
+            counter: this.data[6],
+        };
+    }
+}
+
+export class ProposalEvent {
+    data: bigint[];
+    constructor(data: bigint[]) {
+        this.data = data;
+    }
+
+    static fromEvent(data: BigUint64Array): ProposalEvent {
+        return new ProposalEvent(Array.from(data));
+    }
+
+    toObject(): Proposal {
+        return {
+            marketId: this.data[0],
+            proposer: [this.data[1], this.data[2]],
+            challenger: [this.data[3], this.data[4]],
+            outcome: this.data[5],
+            bond: this.data[6],
+            status: Number(this.data[7]),
+            counter: this.data[8],
+        };
+    }
+}
+
+export class MarketStatusEvent {
+    data: bigint[];
+    constructor(data: bigint[]) {
+        this.data = data;
+    }
+
+    static fromEvent(data: BigUint64Array): MarketStatusEvent {
+        return new MarketStatusEvent(Array.from(data));
+    }
+
+    toObject(): MarketStatusInfo {
+        return {
+            marketId: this.data[0],
+            status: Number(this.data[1]),
+            counter: this.data[2],
+        };
+    }
+}
+
 /* Player related Schema
 // Player Schema
 export interface Player {
@@ -1,7 +1,7 @@
 import { Express } from "express";
 import { Event, EventModel, Service, TxStateManager, TxWitness } from "zkwasm-ts-server";
 import { merkleRootToBeHexString } from "zkwasm-ts-server/src/lib.js";
-import { BetEvent, BetModel, MarketEvent, MarketModel, docToJSON } from "./models.js";
+import { BetEvent, BetModel, CompleteSetEvent, CompleteSetModel, LiquidityEvent, LiquidityModel, MarketEvent, MarketModel, MarketStatusEvent, MarketStatusModel, OrderEvent, OrderModel, ProposalEvent, ProposalModel, ResolutionVoteEvent, ResolutionVoteModel, ShareTransferEvent, ShareTransferModel, docToJSON } from "./models.js";
 import mongoose from 'mongoose';
 
 const service = new Service(eventCallback, batchedCallback, extra);
@@ -86,6 +86,17 @@ service.serve();
 
 const EVENT_MARKET_UPDATE = 2;
 const EVENT_BET_UPDATE = 3;
+const EVENT_MARKET_CREATED = 4;
+const EVENT_ORDER_UPDATE = 5;
+const EVENT_LIQUIDITY_UPDATE = 6;
+const EVENT_RESOLUTION_VOTE = 7;
+const EVENT_PROPOSAL_UPDATE = 8;
+const EVENT_MARKET_STATUS = 9;
+const EVENT_PAUSE_UPDATE = 10;
+const EVENT_CIRCUIT_BREAKER = 11;
+const EVENT_SET_UPDATE = 12;
+const EVENT_SHARE_TRANSFER = 13;
+const EVENT_BALANCE_TRANSFER = 14;
 
 async function batchedCallback(_arg: TxWitness[], _preMerkle: string, postMerkle: string) {
     await txStateManager.moveToCommit(postMerkle);
@@ -136,7 +147,7 @@ async function eventCallback(arg: TxWitness, data: BigUint64Array) {
                     console.log("market update event");
                     let market = MarketEvent.fromEvent(eventData);
                     let marketInfo = market.toObject();
-                    await MarketModel.findOneAndUpdate({counter: marketInfo.counter}, marketInfo, { upsert: true });
+                    await MarketModel.findOneAndUpdate({marketId: marketInfo.marketId, counter: marketInfo.counter}, marketInfo, { upsert: true });
                     console.log("saved market update", market);
                 }
                 break;
@@ -149,6 +160,93 @@ async function eventCallback(arg: TxWitness, data: BigUint64Array) {
                     console.log("saved bet", bet);
                 }
                 break;
+            case EVENT_MARKET_CREATED:
+                {
+                    // [market_id, start_time, end_time, resolution_time, counter, pricing_model, lmsr_b, outcome_count, liquidity...]
+                    console.log("market created event", eventData[0]);
+                }
+                break;
+            case EVENT_ORDER_UPDATE:
+                {
+                    console.log("order update event");
+                    let order = OrderEvent.fromEvent(eventData);
+                    let orderInfo = order.toObject();
+                    await OrderModel.findOneAndUpdate({orderId: orderInfo.orderId}, orderInfo, { upsert: true });
+                    console.log("saved order update", order);
+                }
+                break;
+            case EVENT_LIQUIDITY_UPDATE:
+                {
+                    console.log("liquidity update event");
+                    let liquidity = LiquidityEvent.fromEvent(eventData);
+                    let doc = new LiquidityModel(liquidity.toObject());
+                    await doc.save();
+                    console.log("saved liquidity update", liquidity);
+                }
+                break;
+            case EVENT_RESOLUTION_VOTE:
+                {
+                    console.log("resolution vote event");
+                    let vote = ResolutionVoteEvent.fromEvent(eventData);
+                    let doc = new ResolutionVoteModel(vote.toObject());
+                    await doc.save();
+                    console.log("saved resolution vote", vote);
+                }
+                break;
+            case EVENT_PROPOSAL_UPDATE:
+                {
+                    console.log("proposal update event");
+                    let proposal = ProposalEvent.fromEvent(eventData);
+                    let proposalInfo = proposal.toObject();
+                    await ProposalModel.findOneAndUpdate({marketId: proposalInfo.marketId}, proposalInfo, { upsert: true });
+                    console.log("saved proposal update", proposal);
+                }
+                break;
+            case EVENT_MARKET_STATUS:
+                {
+                    console.log("market status event");
+                    let status = MarketStatusEvent.fromEvent(eventData);
+                    let statusInfo = status.toObject();
+                    await MarketStatusModel.findOneAndUpdate({marketId: statusInfo.marketId}, statusInfo, { upsert: true });
+                    console.log("saved market status", status);
+                }
+                break;
+            case EVENT_PAUSE_UPDATE:
+                {
+                    // [market_id (ALL_MARKETS for every market), trading, claims, counter]
+                    console.log("pause update event", eventData[0], eventData[1], eventData[2]);
+                }
+                break;
+            case EVENT_CIRCUIT_BREAKER:
+                {
+                    // [market_id, action (0 = resumed, 1 = halted), halted_until, counter, outcome_count, ...prices]
+                    console.log("circuit breaker event", eventData[0], eventData[1], eventData[2], eventData.slice(5));
+                }
+                break;
+            case EVENT_SET_UPDATE:
+                {
+                    console.log("complete set event");
+                    let set = CompleteSetEvent.fromEvent(eventData);
+                    let doc = new CompleteSetModel(set.toObject());
+                    await doc.save();
+                    console.log("saved complete set", set);
+                }
+                break;
+            case EVENT_SHARE_TRANSFER:
+                {
+                    console.log("share transfer event");
+                    let transfer = ShareTransferEvent.fromEvent(eventData);
+                    let doc = new ShareTransferModel(transfer.toObject());
+                    await doc.save();
+                    console.log("saved share transfer", transfer);
+                }
+                break;
+            case EVENT_BALANCE_TRANSFER:
+                {
+                    // [from_pid1, from_pid2, to_pid1, to_pid2, amount, counter]
+                    console.log("balance transfer event", eventData[4]);
+                }
+                break;
             default:
                 console.log("unknown event");
                 process.exit(1);
@@ -17,6 +17,9 @@ const RESOLVE = 6;
 const CLAIM = 7;
 const WITHDRAW_FEES = 8;
 
+// The default market seeded at genesis
+const MARKET_ID = 0n;
+
 class Player extends PlayerConvention {
     constructor(key: string, rpc: ZKWasmAppRpc) {
         super(key, rpc, BigInt(DEPOSIT), BigInt(WITHDRAW));
@@ -49,15 +52,15 @@ class Player extends PlayerConvention {
         }
     }
 
-    async placeBet(betType: number, amount: bigint) {
+    async placeBet(marketId: bigint, betType: number, amount: bigint, minSharesOut: bigint = 0n) {
         let nonce = await this.getNonce();
-        let cmd = createCommand(nonce, BigInt(BET), [BigInt(betType), amount]);
+        let cmd = createCommand(nonce, BigInt(BET), [marketId, BigInt(betType), amount, minSharesOut]);
         return await this.sendTransactionWithCommand(cmd);
     }
 
-    async claimWinnings() {
+    async claimWinnings(marketId: bigint) {
         let nonce = await this.getNonce();
-        let cmd = createCommand(nonce, BigInt(CLAIM), []);
+        let cmd = createCommand(nonce, BigInt(CLAIM), [marketId]);
         return await this.sendTransactionWithCommand(cmd);
     }
 
@@ -73,21 +76,21 @@ class Player extends PlayerConvention {
         return await this.sendTransactionWithCommand(cmd);
     }
 
-    async resolveMarket(outcome: boolean) {
+    async resolveMarket(marketId: bigint, outcome: bigint) {
         let nonce = await this.getNonce();
-        let cmd = createCommand(nonce, BigInt(RESOLVE), [outcome ? 1n : 0n]);
+        let cmd = createCommand(nonce, BigInt(RESOLVE), [marketId, outcome]);
         return await this.sendTransactionWithCommand(cmd);
     }
 
-    async withdrawFees() {
+    async withdrawFees(marketId: bigint) {
         let nonce = await this.getNonce();
-        let cmd = createCommand(nonce, BigInt(WITHDRAW_FEES), []);
+        let cmd = createCommand(nonce, BigInt(WITHDRAW_FEES), [marketId]);
         return await this.sendTransactionWithCommand(cmd);
     }
 
-    async sellShares(sellType: number, shares: bigint) {
+    async sellShares(marketId: bigint, sellType: number, shares: bigint, minPayout: bigint = 0n) {
         let nonce = await this.getNonce();
-        let cmd = createCommand(nonce, BigInt(SELL), [BigInt(sellType), shares]);
+        let cmd = createCommand(nonce, BigInt(SELL), [marketId, BigInt(sellType), shares, minPayout]);
         return await this.sendTransactionWithCommand(cmd);
     }
 }
@@ -102,20 +105,22 @@ async function logStateInfo(rpc: any, player: Player, playerName: string, stepDe
         
         if (playerData && playerData.player && playerData.state) {
             const playerInfo = playerData.player.data;
-            const marketInfo = playerData.state.market;
+            const marketInfo = playerData.state.markets.find((m: any) => BigInt(m.market_id) === MARKET_ID).data;
+            const position = playerInfo.positions.find((p: any) => BigInt(p.market_id) === MARKET_ID)
+                ?? { shares: [0, 0], claimed: false };
             
             console.log(`${playerName} Balance: ${playerInfo.balance}`);
-            console.log(`${playerName} YES Shares: ${playerInfo.yes_shares}`);
-            console.log(`${playerName} NO Shares: ${playerInfo.no_shares}`);
-            console.log(`${playerName} Claimed: ${playerInfo.claimed}`);
+            console.log(`${playerName} YES Shares: ${position.shares[1] ?? 0}`);
+            console.log(`${playerName} NO Shares: ${position.shares[0] ?? 0}`);
+            console.log(`${playerName} Claimed: ${position.claimed}`);
             
-            console.log(`Market YES Liquidity: ${marketInfo.yes_liquidity}`);
-            console.log(`Market NO Liquidity: ${marketInfo.no_liquidity}`);
+            console.log(`Market YES Liquidity: ${marketInfo.liquidity[1]}`);
+            console.log(`Market NO Liquidity: ${marketInfo.liquidity[0]}`);
             console.log(`Market Total Volume: ${marketInfo.total_volume}`);
             console.log(`Market Total Fees: ${marketInfo.total_fees_collected}`);
-            console.log(`Market Resolved: ${marketInfo.resolved}`);
-            if (marketInfo.resolved) {
-                console.log(`Market Outcome: ${marketInfo.outcome ? 'YES' : 'NO'}`);
+            console.log(`Market Status: ${marketInfo.status}`);
+            if (marketInfo.outcome !== null) {
+                console.log(`Market Outcome: ${marketInfo.outcomes[marketInfo.outcome]}`);
             }
         }
     } catch (error) {
@@ -237,7 +242,7 @@ async function testPredictionMarket() {
         console.log("\n=== STEP 3: Player1 Places YES Bets ===");
         
         try {
-            await player1.placeBet(1, 1000n); // YES bet
+            await player1.placeBet(MARKET_ID, 1, 1000n); // YES bet
             console.log("Player1 bet 1000 on YES");
         } catch (error) {
             console.log("Player1 first YES bet error:", error instanceof Error ? error.message : error);
@@ -245,7 +250,7 @@ async function testPredictionMarket() {
         await logStateInfo(rpc, player1, "Player1", "After First YES Bet");
         
         try {
-            await player1.placeBet(1, 500n); // Another YES bet
+            await player1.placeBet(MARKET_ID, 1, 500n); // Another YES bet
             console.log("Player1 bet 500 more on YES");
         } catch (error) {
             console.log("Player1 second YES bet error:", error instanceof Error ? error.message : error);
@@ -256,7 +261,7 @@ async function testPredictionMarket() {
         console.log("\n=== STEP 4: Player2 Places NO Bets ===");
         
         try {
-            await player2.placeBet(0, 800n); // NO bet
+            await player2.placeBet(MARKET_ID, 0, 800n); // NO bet
             console.log("Player2 bet 800 on NO");
         } catch (error) {
             console.log("Player2 first NO bet error:", error instanceof Error ? error.message : error);
@@ -264,7 +269,7 @@ async function testPredictionMarket() {
         await logStateInfo(rpc, player2, "Player2", "After First NO Bet");
         
         try {
-            await player2.placeBet(0, 600n); // Another NO bet
+            await player2.placeBet(MARKET_ID, 0, 600n); // Another NO bet
             console.log("Player2 bet 600 more on NO");
         } catch (error) {
             console.log("Player2 second NO bet error:", error instanceof Error ? error.message : error);
@@ -275,7 +280,7 @@ async function testPredictionMarket() {
         console.log("\n=== STEP 5: Player1 Also Bets on NO ===");
         
         try {
-            await player1.placeBet(0, 700n); // NO bet
+            await player1.placeBet(MARKET_ID, 0, 700n); // NO bet
             console.log("Player1 bet 700 on NO");
         } catch (error) {
             console.log("Player1 NO bet error:", error instanceof Error ? error.message : error);
@@ -287,7 +292,7 @@ async function testPredictionMarket() {
         
         // Player1 sells some YES shares
         try {
-            await player1.placeBet(1, 300n); // First buy more YES shares to have enough to sell
+            await player1.placeBet(MARKET_ID, 1, 300n); // First buy more YES shares to have enough to sell
             console.log("Player1 bought 300 more YES shares");
         } catch (error) {
             console.log("Player1 additional YES bet error:", error instanceof Error ? error.message : error);
@@ -296,7 +301,7 @@ async function testPredictionMarket() {
         
         // Now sell some YES shares (sell type 1 = YES)
         try {
-            await player1.sellShares(1, 200n); // Sell 200 YES shares
+            await player1.sellShares(MARKET_ID, 1, 200n); // Sell 200 YES shares
             console.log("Player1 sold 200 YES shares");
         } catch (error) {
             console.log("Player1 YES sell error:", error instanceof Error ? error.message : error);
@@ -305,7 +310,7 @@ async function testPredictionMarket() {
         
         // Player2 sells some NO shares (sell type 0 = NO)
         try {
-            await player2.sellShares(0, 150n); // Sell 150 NO shares
+            await player2.sellShares(MARKET_ID, 0, 150n); // Sell 150 NO shares
             console.log("Player2 sold 150 NO shares");
         } catch (error) {
             console.log("Player2 NO sell error:", error instanceof Error ? error.message : error);
@@ -314,7 +319,7 @@ async function testPredictionMarket() {
         
         // Player1 also sells some NO shares
         try {
-            await player1.sellShares(0, 100n); // Sell 100 NO shares
+            await player1.sellShares(MARKET_ID, 0, 100n); // Sell 100 NO shares
             console.log("Player1 sold 100 NO shares");
         } catch (error) {
             console.log("Player1 NO sell error:", error instanceof Error ? error.message : error);
@@ -324,19 +329,26 @@ async function testPredictionMarket() {
         // Step 7: Market resolution
         console.log("\n=== STEP 7: Admin Resolves Market (YES Wins) ===");
         
-        await admin.resolveMarket(true); // YES outcome
-        console.log("Market resolved: YES wins");
+        // Rejected with MarketNotEnded until the market's resolution time has passed
+        try {
+            await admin.resolveMarket(MARKET_ID, 1n); // YES outcome
+            console.log("Market resolved: YES wins");
+        } catch (error) {
+            console.log("Resolve error:", error instanceof Error ? error.message : error);
+        }
         await logStateInfo(rpc, admin, "Admin", "After Market Resolution");
         
-        // Step 8: Players claim winnings
+        // Step 8: Players claim winnings (rejected with MarketNotResolved until the dispute window has passed)
         console.log("\n=== STEP 8: Players Claim Winnings ===");
         
         try {
-            await player1.claimWinnings();
+            await player1.claimWinnings(MARKET_ID);
             console.log("Player1 claimed winnings");
         } catch (error) {
             if (error instanceof Error && error.message === "NoWinningPosition") {
                 console.log("Player1 has no winning position to claim");
+            } else if (error instanceof Error && error.message === "MarketNotResolved") {
+                console.log("Player1 claim rejected: outcome still in its dispute window");
             } else {
                 console.log("Player1 claim error:", error);
             }
@@ -344,11 +356,13 @@ async function testPredictionMarket() {
         await logStateInfo(rpc, player1, "Player1", "After Claiming Attempt");
         
         try {
-            await player2.claimWinnings();
+            await player2.claimWinnings(MARKET_ID);
             console.log("Player2 claimed winnings");
         } catch (error) {
             if (error instanceof Error && error.message === "NoWinningPosition") {
                 console.log("Player2 has no winning position to claim");
+            } else if (error instanceof Error && error.message === "MarketNotResolved") {
+                console.log("Player2 claim rejected: outcome still in its dispute window");
             } else {
                 console.log("Player2 claim error:", error);
             }
@@ -358,7 +372,7 @@ async function testPredictionMarket() {
         // Step 9: Admin withdraws fees
         console.log("\n=== STEP 9: Admin Withdraws Fees ===");
         
-        await admin.withdrawFees();
+        await admin.withdrawFees(MARKET_ID);
         console.log("Admin withdrew collected fees");
         await logStateInfo(rpc, admin, "Admin", "After Fee Withdrawal");
         